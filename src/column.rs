@@ -4,10 +4,13 @@ use std::fmt;
 use crate::keywords::{escape_if_keyword};
 use crate::{
     SqlType,
+    escape_identifier,
+    escape_string,
     create::{
         CodecList,
         ColumnTTL,
     },
+    expression::Expr,
 };
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -23,20 +26,20 @@ impl fmt::Display for Column {
             write!(
                 f,
                 "{}.{}",
-                escape_if_keyword(table),
-                escape_if_keyword(&self.name)
+                escape_identifier(table),
+                escape_identifier(&self.name)
             )?;
         } else {
-            write!(f, "{}", escape_if_keyword(&self.name))?;
+            write!(f, "{}", escape_identifier(&self.name))?;
         }
         if let Some(ref alias) = self.alias {
-            write!(f, " AS {}", escape_if_keyword(alias))?;
+            write!(f, " AS {}", escape_identifier(alias))?;
         }
         Ok(())
     }
 }
 
-impl<'a> From<&'a str> for Column {
+impl From<&str> for Column {
     fn from(c: &str) -> Column {
         match c.find(".") {
             None => Column {
@@ -55,18 +58,18 @@ impl<'a> From<&'a str> for Column {
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ColumnOption {
-    DefaultValue(String),
-    Materialized(String),
+    DefaultValue(Expr),
+    Materialized(Expr),
 }
 
 impl fmt::Display for ColumnOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            ColumnOption::DefaultValue(ref literal) => {
-                write!(f, "DEFAULT {}", literal.to_string())
+            ColumnOption::DefaultValue(ref expr) => {
+                write!(f, "DEFAULT {}", expr)
             }
-            ColumnOption::Materialized(ref literal) => {
-                write!(f, "MATERIALIZED {}", literal.to_string())
+            ColumnOption::Materialized(ref expr) => {
+                write!(f, "MATERIALIZED {}", expr)
             }
         }
     }
@@ -78,26 +81,18 @@ pub struct ColumnSpecification {
     pub sql_type: SqlType,
     pub codec: Option<CodecList>,
     pub ttl: Option<ColumnTTL>,
-    pub nullable: bool,
     pub option: Option<ColumnOption>,
     pub comment: Option<String>,
-    pub lowcardinality: bool,
 }
 
 impl fmt::Display for ColumnSpecification {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "`{}` ", escape_if_keyword(&self.column.name))?;
-        match (self.lowcardinality, self.nullable) {
-            (false,false) => write!(f, "{}", self.sql_type),
-            (true,false) => write!(f, "LowCardinality({})", self.sql_type),
-            (false,true) => write!(f, "Nullable({})", self.sql_type),
-            (true,true) => write!(f, "LowCardinality(Nullable({}))", self.sql_type),
-        }?;
+        write!(f, "`{}` {}", escape_if_keyword(&self.column.name), self.sql_type)?;
         if let Some(ref opt) = self.option {
             write!(f, " {}", opt)?;
         }
         if let Some(ref comment) = self.comment {
-            write!(f, " COMMENT '{}'", comment)?;
+            write!(f, " COMMENT {}", escape_string(comment))?;
         }
         if let Some(ref codec) = self.codec {
             write!(f, " CODEC({})",
@@ -122,10 +117,8 @@ impl ColumnSpecification {
             sql_type,
             codec: None,
             ttl: None,
-            nullable: false,
             option: None,
             comment: None,
-            lowcardinality: false,
         }
     }
 }
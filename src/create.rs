@@ -4,26 +4,39 @@ use std::str::FromStr;
 use std::fmt; 
 
 use nom::{
-    IResult,
-    error::{ ErrorKind, ParseError},
+    Err as NomErr,
+    error::{ context, ErrorKind, ParseError},
     branch::alt,
-    sequence::{delimited, preceded, tuple},
-    combinator::{map, opt, recognize},
-    character::complete::{digit1, multispace0, multispace1, one_of, },
-    bytes::complete::{tag, tag_no_case, take_until, },
-    multi::{many0, separated_list,},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    combinator::{cut, map, map_res, opt, recognize, verify},
+    character::complete::{digit1, one_of, },
+    bytes::complete::{tag, tag_no_case},
+    multi::{separated_list, separated_nonempty_list},
 };
 
 use crate::{
+    IResult,
+    SyntaxError,
+    multispace0,
+    multispace1,
+    correct_identifier,
+    escape_identifier,
+    escape_string,
+    raw_string_single_quoted,
+    schema_table_reference_no_alias,
     sql_identifier,
     ws_sep_comma,
     column_identifier_no_alias,
-    SqlTypeOpts,
     type_identifier,
-    ttl_expression,
-    statement_terminator,
-    schema_table_reference,
-    sql_expression,
+};
+use crate::expression::{
+    Expr,
+    Function,
+    Literal,
+    expression,
+    function_call,
+    interval_expression,
+    literal,
 };
 use crate::column::{
     ColumnSpecification,
@@ -31,33 +44,344 @@ use crate::column::{
     Column,
 };
 use crate::table::Table;
+use crate::select::{SelectQuery, selection, setting};
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct CreateTableStatement {
     pub table: Table,
-    pub fields: Vec<ColumnSpecification>,
+    pub if_not_exists: bool,
+    pub or_replace: bool,
+    pub temporary: bool,
+    pub on_cluster: Option<String>,
+    pub source: CreateTableSource,
     //pub indexes: Vec<..>,
-    pub engine: Engine,
+    // a table created AS a table function has no engine, a temporary table
+    // without one uses Memory
+    pub engine: Option<Engine>,
 }
 
 impl fmt::Display for CreateTableStatement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "CREATE TABLE {} (\n",
-            self.table
-        )?;
-        write!(f, "  {}",
-            self.fields
-                .iter()
-                .map(|c| format!("{}", c)) 
-                .collect::<Vec<String>>()
-                .join(",\n  ")
-        )?;
-        write!(f,"\n) {};", self.engine)
+        write!(f, "CREATE ")?;
+        if self.or_replace {
+            write!(f, "OR REPLACE ")?;
+        }
+        if self.temporary {
+            write!(f, "TEMPORARY ")?;
+        }
+        write!(f, "TABLE ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", self.table)?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        match self.source {
+            CreateTableSource::Columns(ref fields) => {
+                writeln!(f, " (")?;
+                write!(f, "  {}",
+                    fields
+                        .iter()
+                        .map(|c| format!("{}", c))
+                        .collect::<Vec<String>>()
+                        .join(",\n  ")
+                )?;
+                write!(f, "\n)")?;
+                if let Some(ref engine) = self.engine {
+                    write!(f, " {}", engine)?;
+                }
+            },
+            CreateTableSource::Table(ref table) => {
+                write!(f, " AS {}", table)?;
+                if let Some(ref engine) = self.engine {
+                    write!(f, " {}", engine)?;
+                }
+            },
+            CreateTableSource::TableFunction(ref function) => write!(f, " AS {}", function)?,
+            CreateTableSource::Select(ref select) => {
+                if let Some(ref engine) = self.engine {
+                    write!(f, " {}", engine)?;
+                }
+                write!(f, " AS {}", select)?;
+            },
+        }
+        write!(f, ";")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CreateTableSource {
+    // explicit column list
+    Columns(Vec<ColumnSpecification>),
+    // AS [db.]table, structure is cloned from the other table
+    Table(Table),
+    // AS table_function(...)
+    TableFunction(Function),
+    // ENGINE = engine AS SELECT ...
+    Select(SelectQuery),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CreateMaterializedViewStatement {
+    pub view: Table,
+    pub if_not_exists: bool,
+    pub on_cluster: Option<String>,
+    // table receiving the data, the view has its own storage otherwise
+    pub to: Option<Table>,
+    pub fields: Vec<ColumnSpecification>,
+    pub engine: Option<Engine>,
+    pub populate: bool,
+    pub select: SelectQuery,
+}
+
+impl fmt::Display for CreateMaterializedViewStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CREATE MATERIALIZED VIEW ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", self.view)?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        if let Some(ref to) = self.to {
+            write!(f, " TO {}", to)?;
+        }
+        write!(f, "{}", view_fields(&self.fields))?;
+        if let Some(ref engine) = self.engine {
+            write!(f, " {}", engine)?;
+        }
+        if self.populate {
+            write!(f, " POPULATE")?;
+        }
+        write!(f, " AS {};", self.select)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CreateViewStatement {
+    pub view: Table,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub on_cluster: Option<String>,
+    pub fields: Vec<ColumnSpecification>,
+    pub select: SelectQuery,
+}
+
+impl fmt::Display for CreateViewStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CREATE ")?;
+        if self.or_replace {
+            write!(f, "OR REPLACE ")?;
+        }
+        write!(f, "VIEW ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", self.view)?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        write!(f, "{} AS {};", view_fields(&self.fields), self.select)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CreateLiveViewStatement {
+    pub view: Table,
+    pub if_not_exists: bool,
+    pub on_cluster: Option<String>,
+    // `WITH TIMEOUT [seconds]`, the period is optional
+    pub timeout: Option<Option<u64>>,
+    // `WITH REFRESH [seconds]`, the period is optional
+    pub refresh: Option<Option<u64>>,
+    pub fields: Vec<ColumnSpecification>,
+    pub select: SelectQuery,
+}
+
+impl fmt::Display for CreateLiveViewStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CREATE LIVE VIEW ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", self.view)?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        let period = |p: &Option<u64>| p.map(|p| format!(" {}", p)).unwrap_or_default();
+        match (&self.timeout, &self.refresh) {
+            (Some(t), Some(r)) => write!(f, " WITH TIMEOUT{} AND REFRESH{}", period(t), period(r))?,
+            (Some(t), None) => write!(f, " WITH TIMEOUT{}", period(t))?,
+            (None, Some(r)) => write!(f, " WITH REFRESH{}", period(r))?,
+            (None, None) => (),
+        }
+        write!(f, "{} AS {};", view_fields(&self.fields), self.select)
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CreateWindowViewStatement {
+    pub view: Table,
+    pub if_not_exists: bool,
+    pub on_cluster: Option<String>,
+    pub to: Option<Table>,
+    pub fields: Vec<ColumnSpecification>,
+    pub inner_engine: Option<Engine>,
+    pub engine: Option<Engine>,
+    // STRICTLY_ASCENDING, ASCENDING or INTERVAL
+    pub watermark: Option<String>,
+    pub allowed_lateness: Option<String>,
+    pub populate: bool,
+    pub select: SelectQuery,
+}
+
+impl fmt::Display for CreateWindowViewStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CREATE WINDOW VIEW ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", self.view)?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        if let Some(ref to) = self.to {
+            write!(f, " TO {}", to)?;
+        }
+        write!(f, "{}", view_fields(&self.fields))?;
+        if let Some(ref engine) = self.inner_engine {
+            write!(f, " INNER {}", engine)?;
+        }
+        if let Some(ref engine) = self.engine {
+            write!(f, " {}", engine)?;
+        }
+        if let Some(ref watermark) = self.watermark {
+            write!(f, " WATERMARK = {}", watermark)?;
+        }
+        if let Some(ref lateness) = self.allowed_lateness {
+            write!(f, " ALLOWED_LATENESS = {}", lateness)?;
+        }
+        if self.populate {
+            write!(f, " POPULATE")?;
+        }
+        write!(f, " AS {};", self.select)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CreateDatabaseStatement {
+    pub name: String,
+    pub if_not_exists: bool,
+    pub on_cluster: Option<String>,
+    pub engine: Option<DatabaseEngine>,
+    pub comment: Option<String>,
+}
+
+impl fmt::Display for CreateDatabaseStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CREATE DATABASE ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{}", escape_identifier(&self.name))?;
+        if let Some(ref cluster) = self.on_cluster {
+            write!(f, " ON CLUSTER {}", escape_cluster_name(cluster))?;
+        }
+        if let Some(ref engine) = self.engine {
+            write!(f, " ENGINE = {}", engine)?;
+        }
+        if let Some(ref comment) = self.comment {
+            write!(f, " COMMENT {}", escape_string(comment))?;
+        }
+        write!(f, ";")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DatabaseEngine {
+    Atomic,
+    Ordinary,
+    // expiration time in seconds
+    Lazy(u64),
+    Replicated {
+        zoo_path: String,
+        shard: String,
+        replica: String,
+    },
+    MySQL {
+        host: String,
+        database: String,
+        user: String,
+        password: String,
+    },
+    PostgreSQL {
+        host: String,
+        database: String,
+        user: String,
+        password: String,
+        schema: Option<String>,
+        use_table_cache: Option<bool>,
+    },
+}
+
+impl fmt::Display for DatabaseEngine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseEngine::Atomic => write!(f, "Atomic"),
+            DatabaseEngine::Ordinary => write!(f, "Ordinary"),
+            DatabaseEngine::Lazy(expiration) => write!(f, "Lazy({})", expiration),
+            DatabaseEngine::Replicated { zoo_path, shard, replica } => write!(f, "Replicated({}, {}, {})",
+                escape_string(zoo_path),
+                escape_string(shard),
+                escape_string(replica),
+            ),
+            DatabaseEngine::MySQL { host, database, user, password } => write!(f, "MySQL({}, {}, {}, {})",
+                escape_string(host),
+                escape_string(database),
+                escape_string(user),
+                escape_string(password),
+            ),
+            DatabaseEngine::PostgreSQL { host, database, user, password, schema, use_table_cache } => {
+                write!(f, "PostgreSQL({}, {}, {}, {}",
+                    escape_string(host),
+                    escape_string(database),
+                    escape_string(user),
+                    escape_string(password),
+                )?;
+                if let Some(ref schema) = schema {
+                    write!(f, ", {}", escape_string(schema))?;
+                    if let Some(cache) = use_table_cache {
+                        write!(f, ", {}", *cache as u8)?;
+                    }
+                }
+                write!(f, ")")
+            },
+        }
+    }
+}
+
+fn view_fields(fields: &[ColumnSpecification]) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+    format!(" (\n  {}\n)",
+        fields
+            .iter()
+            .map(|c| format!("{}", c))
+            .collect::<Vec<String>>()
+            .join(",\n  ")
+    )
+}
+
+// Cluster names are often macros like `{cluster}` which need quoting
+fn escape_cluster_name(name: &str) -> String {
+    match correct_identifier(name.as_bytes()) {
+        Ok((&[], _)) => name.to_owned(),
+        _ => escape_string(name),
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum CodecError<I> {
@@ -145,34 +469,11 @@ impl fmt::Display for CodecList {
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ColumnTTL {
-    column: String,
-    interval: Option<String>,
+    pub expr: Expr,
 }
 impl fmt::Display for ColumnTTL {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"TTL {}", self.column)?;
-
-        match self.interval {
-            Some(ref interval) => write!(f, " + {}", interval),
-            None => Ok(()),
-        }
-    }
-}
-
-impl<'a> From<&'a str> for ColumnTTL {
-    fn from(t: &str) -> ColumnTTL {
-        ColumnTTL {
-            column: String::from(t),
-            interval: None,
-        }
-    }
-}
-impl<'a> From<(&'a str, &'a str)> for ColumnTTL {
-    fn from(t: (&str, &str)) -> ColumnTTL {
-        ColumnTTL {
-            column: String::from(t.0),
-            interval: Some(String::from(t.1)),
-        }
+        write!(f, "TTL {}", self.expr)
     }
 }
 
@@ -203,7 +504,7 @@ pub struct EngineDistributed {
     table: String,
     // The sharding expression can be any expression from constants and table
     // columns that returns an integer.
-    sharding_key: Option<String>,
+    sharding_key: Option<Expr>,
     policy_name: Option<String>,
 }
 
@@ -227,72 +528,667 @@ impl fmt::Display for EngineDistributed {
     }
 }
 
+// TTL rule of a MergeTree table: `expr [DELETE|TO DISK 'x'|TO VOLUME 'x']`
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct EngineMergeTree(String);
+pub enum TtlAction {
+    Delete,
+    ToDisk(String),
+    ToVolume(String),
+}
+
+impl fmt::Display for TtlAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TtlAction::Delete => write!(f, "DELETE"),
+            TtlAction::ToDisk(disk) => write!(f, "TO DISK {}", escape_string(disk)),
+            TtlAction::ToVolume(volume) => write!(f, "TO VOLUME {}", escape_string(volume)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TtlRule {
+    pub expr: Expr,
+    pub action: Option<TtlAction>,
+}
+
+impl fmt::Display for TtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        if let Some(ref action) = self.action {
+            write!(f, " {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+// Clauses following a MergeTree family engine, printed in the order of
+// SHOW CREATE TABLE
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MergeTreeClauses {
+    pub partition_by: Option<Expr>,
+    // a tuple for compound keys, ORDER BY (a, b)
+    pub order_by: Option<Expr>,
+    pub primary_key: Option<Expr>,
+    pub sample_by: Option<Expr>,
+    pub ttl: Vec<TtlRule>,
+    pub settings: Vec<(String, Expr)>,
+}
+
+impl fmt::Display for MergeTreeClauses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref expr) = self.partition_by {
+            write!(f, " PARTITION BY {}", expr)?;
+        }
+        if let Some(ref expr) = self.primary_key {
+            write!(f, " PRIMARY KEY {}", expr)?;
+        }
+        if let Some(ref expr) = self.order_by {
+            write!(f, " ORDER BY {}", expr)?;
+        }
+        if let Some(ref expr) = self.sample_by {
+            write!(f, " SAMPLE BY {}", expr)?;
+        }
+        if !self.ttl.is_empty() {
+            write!(f, " TTL {}",
+                self.ttl
+                    .iter()
+                    .map(|rule| rule.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        if !self.settings.is_empty() {
+            write!(f, " SETTINGS {}",
+                self.settings
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Engines of the MergeTree family with their specific parameters
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MergeTreeKind {
+    MergeTree,
+    // ReplacingMergeTree([ver[, is_deleted]])
+    Replacing {
+        version: Option<Expr>,
+        is_deleted: Option<Expr>,
+    },
+    // SummingMergeTree([columns]), all numeric columns are summed by default
+    Summing {
+        columns: Vec<Expr>,
+    },
+    Aggregating,
+    Collapsing {
+        sign: Expr,
+    },
+    VersionedCollapsing {
+        sign: Expr,
+        version: Expr,
+    },
+    // GraphiteMergeTree('graphite_rollup') names a server config section
+    Graphite {
+        config_section: Literal,
+    },
+}
+
+impl MergeTreeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MergeTreeKind::MergeTree => "MergeTree",
+            MergeTreeKind::Replacing { .. } => "ReplacingMergeTree",
+            MergeTreeKind::Summing { .. } => "SummingMergeTree",
+            MergeTreeKind::Aggregating => "AggregatingMergeTree",
+            MergeTreeKind::Collapsing { .. } => "CollapsingMergeTree",
+            MergeTreeKind::VersionedCollapsing { .. } => "VersionedCollapsingMergeTree",
+            MergeTreeKind::Graphite { .. } => "GraphiteMergeTree",
+        }
+    }
+
+    fn params(&self) -> Vec<String> {
+        match self {
+            MergeTreeKind::MergeTree | MergeTreeKind::Aggregating => vec![],
+            MergeTreeKind::Replacing { version, is_deleted } => version
+                .iter()
+                .chain(is_deleted.iter())
+                .map(|c| c.to_string())
+                .collect(),
+            MergeTreeKind::Summing { columns } => match columns.len() {
+                0 => vec![],
+                1 => vec![columns[0].to_string()],
+                _ => vec![format!("({})",
+                    columns
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )],
+            },
+            MergeTreeKind::Collapsing { sign } => vec![sign.to_string()],
+            MergeTreeKind::VersionedCollapsing { sign, version } =>
+                vec![sign.to_string(), version.to_string()],
+            MergeTreeKind::Graphite { config_section } => vec![config_section.to_string()],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EngineMergeTree {
+    pub kind: MergeTreeKind,
+    pub clauses: MergeTreeClauses,
+}
 
 impl fmt::Display for EngineMergeTree {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // write `self` cause `stack overflow` - why?
-        write!(f,"{}", self.0)
+        write!(f, "{}", self.kind.name())?;
+        let params = self.kind.params();
+        if !params.is_empty() {
+            write!(f, "({})", params.join(", "))?;
+        }
+        write!(f, "{}", self.clauses)
     }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct EngineReplicatedMergeTree(String);
+pub struct EngineReplicatedMergeTree {
+    // both are omitted to use the server defaults
+    pub zookeeper_path: Option<String>,
+    pub replica_name: Option<String>,
+    pub kind: MergeTreeKind,
+    pub clauses: MergeTreeClauses,
+}
 
 impl fmt::Display for EngineReplicatedMergeTree {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // write `self` cause `stack overflow` - why?
-        write!(f,"{}", self.0)
+        let mut params: Vec<String> = self.zookeeper_path
+            .iter()
+            .chain(self.replica_name.iter())
+            .map(|s| escape_string(s))
+            .collect();
+        params.extend(self.kind.params());
+        write!(f, "Replicated{}", self.kind.name())?;
+        if !params.is_empty() {
+            write!(f, "({})", params.join(", "))?;
+        }
+        write!(f, "{}", self.clauses)
     }
 }
 
 pub fn creation(i: &[u8]) -> IResult<&[u8], CreateTableStatement>
 {
-    let (remaining_input, (_, _, _, _, table, _, _, _, fields_list, _, _, _, engine, _)) =
+    // CREATE [OR REPLACE] [TEMPORARY] TABLE [IF NOT EXISTS] [db.]table_name [ON CLUSTER cluster] (...)
+    let (i, (_, or_replace, temporary, _, _, if_not_exists, _, table, on_cluster)) =
         tuple((
             tag_no_case("create"),
+            or_replace,
+            map(opt(preceded(multispace1, tag_no_case("temporary"))), |t| t.is_some()),
             multispace1,
             tag_no_case("table"),
+            if_not_exists,
             multispace1,
-            schema_table_reference,
+            context("table name", schema_table_reference_no_alias),
+            on_cluster,
+        ))(i)?;
+    let (remaining_input, (source, engine)) = alt((
+        table_source_columns(temporary),
+        table_source_select,
+        table_source_clone,
+    ))(i)?;
+
+    // attach table names to columns:
+    let source = match source {
+        CreateTableSource::Columns(fields) => CreateTableSource::Columns(fields
+            .into_iter()
+            .map(|field| {
+                let column = Column {
+                    table: Some(table.name.clone()),
+                    ..field.column
+                };
+
+                ColumnSpecification { column, ..field }
+            })
+            .collect()
+        ),
+        source => source,
+    };
+
+    Ok((
+        remaining_input,
+        CreateTableStatement {
+            table,
+            if_not_exists,
+            or_replace,
+            temporary,
+            on_cluster,
+            source,
+            //indexes,
+            engine,
+        },
+    ))
+}
+
+// (columns...) ENGINE = engine, the engine of temporary tables defaults to Memory
+fn table_source_columns<'a>(temporary: bool) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], (CreateTableSource, Option<Engine>)> {
+    move |i| {
+        let (i, (_, _, _, fields, _, _)) = tuple((
             multispace0,
-            tag("("),
+            context("'('", tag("(")),
             multispace0,
             field_specification_list,
             multispace0,
-            tag(")"),
-            multispace0,
-            engine_spec,
-            opt(statement_terminator),
+            context("column definition or ')'", tag(")")),
         ))(i)?;
+        let (i, engine) = if temporary {
+            opt(preceded(multispace0, engine_spec))(i)?
+        } else {
+            map(preceded(multispace0, context("ENGINE clause", engine_spec)), Some)(i)?
+        };
+        Ok((i, (CreateTableSource::Columns(fields), engine)))
+    }
+}
 
-    // "table AS alias" isn't legal in CREATE statements
-    assert!(table.alias.is_none());
-    // attach table names to columns:
-    let fields = fields_list
-        .into_iter()
-        .map(|field| {
-            let column = Column {
-                table: Some(table.name.clone()),
-                ..field.column
-            };
+// ENGINE = engine AS SELECT ...
+fn table_source_select(i: &[u8]) -> IResult<&[u8], (CreateTableSource, Option<Engine>)> {
+    map(
+        tuple((multispace1, engine_spec, as_select)),
+        |(_, engine, select)| (CreateTableSource::Select(select), Some(engine))
+    )(i)
+}
 
-            ColumnSpecification { column, ..field }
-        })
-        .collect();
+// AS [db.]table [ENGINE = engine] or AS table_function(...)
+fn table_source_clone(i: &[u8]) -> IResult<&[u8], (CreateTableSource, Option<Engine>)> {
+    preceded(
+        tuple((multispace1, tag_no_case("as"), multispace1)),
+        context("table or table function", alt((
+            map(function_call, |f| (CreateTableSource::TableFunction(f), None)),
+            map(
+                pair(schema_table_reference_no_alias, opt(preceded(multispace1, engine_spec))),
+                |(table, engine)| (CreateTableSource::Table(table), engine)
+            ),
+        ))),
+    )(i)
+}
+
+pub fn materialized_view_creation(i: &[u8]) -> IResult<&[u8], CreateMaterializedViewStatement> {
+    // CREATE MATERIALIZED VIEW [IF NOT EXISTS] [db.]table_name [ON CLUSTER] [TO[db.]name]
+    //   [ENGINE = engine] [POPULATE] AS SELECT ...
+    let (i, (_, _, _, _, _, if_not_exists, _, view, on_cluster, to, fields)) =
+        tuple((
+            tag_no_case("create"),
+            multispace1,
+            tag_no_case("materialized"),
+            multispace1,
+            tag_no_case("view"),
+            if_not_exists,
+            multispace1,
+            context("view name", schema_table_reference_no_alias),
+            on_cluster,
+            opt(preceded(
+                tuple((multispace1, tag_no_case("to"), multispace1)),
+                context("table name", schema_table_reference_no_alias),
+            )),
+            view_field_list,
+        ))(i)?;
+    // the TO table stores the data, so it excludes ENGINE and POPULATE
+    let (remaining_input, (engine, populate, select)) = if to.is_some() {
+        map(as_select, |select| (None, false, select))(i)?
+    } else {
+        tuple((opt(preceded(multispace1, engine_spec)), populate, as_select))(i)?
+    };
 
     Ok((
         remaining_input,
-        CreateTableStatement {
-            table,
+        CreateMaterializedViewStatement {
+            view,
+            if_not_exists,
+            on_cluster,
+            to,
             fields,
-            //indexes,
             engine,
+            populate,
+            select,
+        },
+    ))
+}
+
+pub fn view_creation(i: &[u8]) -> IResult<&[u8], CreateViewStatement> {
+    // CREATE [OR REPLACE] VIEW [IF NOT EXISTS] [db.]table_name [ON CLUSTER] AS SELECT ...
+    let (remaining_input, (_, or_replace, _, _, if_not_exists, _, view, on_cluster, fields, select)) =
+        tuple((
+            tag_no_case("create"),
+            or_replace,
+            multispace1,
+            tag_no_case("view"),
+            if_not_exists,
+            multispace1,
+            context("view name", schema_table_reference_no_alias),
+            on_cluster,
+            view_field_list,
+            as_select,
+        ))(i)?;
+
+    Ok((
+        remaining_input,
+        CreateViewStatement {
+            view,
+            or_replace,
+            if_not_exists,
+            on_cluster,
+            fields,
+            select,
+        },
+    ))
+}
+
+pub fn live_view_creation(i: &[u8]) -> IResult<&[u8], CreateLiveViewStatement> {
+    // CREATE LIVE VIEW [IF NOT EXISTS] [db.]table_name
+    //   [WITH [TIMEOUT [value_in_sec] [AND]] [REFRESH [value_in_sec]]] AS SELECT ...
+    let (remaining_input, (_, _, _, _, _, if_not_exists, _, view, on_cluster, with, fields, select)) =
+        tuple((
+            tag_no_case("create"),
+            multispace1,
+            tag_no_case("live"),
+            multispace1,
+            tag_no_case("view"),
+            if_not_exists,
+            multispace1,
+            context("view name", schema_table_reference_no_alias),
+            on_cluster,
+            opt(preceded(
+                tuple((multispace1, tag_no_case("with"), multispace1)),
+                context("TIMEOUT or REFRESH", alt((
+                    map(
+                        tuple((
+                            preceded(tag_no_case("timeout"), live_view_period),
+                            opt(preceded(
+                                tuple((multispace1, tag_no_case("and"), multispace1, tag_no_case("refresh"))),
+                                live_view_period,
+                            )),
+                        )),
+                        |(timeout, refresh)| (Some(timeout), refresh),
+                    ),
+                    map(
+                        preceded(tag_no_case("refresh"), live_view_period),
+                        |refresh| (None, Some(refresh))
+                    ),
+                ))),
+            )),
+            view_field_list,
+            as_select,
+        ))(i)?;
+
+    let (timeout, refresh) = with.unwrap_or((None, None));
+    Ok((
+        remaining_input,
+        CreateLiveViewStatement {
+            view,
+            if_not_exists,
+            on_cluster,
+            timeout,
+            refresh,
+            fields,
+            select,
+        },
+    ))
+}
+
+fn live_view_period(i: &[u8]) -> IResult<&[u8], Option<u64>> {
+    opt(preceded(
+        multispace1,
+        map_res(digit1, |d| u64::from_str(str::from_utf8(d).unwrap()))
+    ))(i)
+}
+
+pub fn window_view_creation(i: &[u8]) -> IResult<&[u8], CreateWindowViewStatement> {
+    // CREATE WINDOW VIEW [IF NOT EXISTS] [db.]table_name [TO [db.]table_name]
+    //   [INNER ENGINE engine] [ENGINE engine] [WATERMARK strategy]
+    //   [ALLOWED_LATENESS interval_function] [POPULATE] AS SELECT ...
+    let (remaining_input, (_, _, _, _, _, if_not_exists, _, view, on_cluster, to, fields, inner_engine, engine, watermark, allowed_lateness, populate, select)) =
+        tuple((
+            tag_no_case("create"),
+            multispace1,
+            tag_no_case("window"),
+            multispace1,
+            tag_no_case("view"),
+            if_not_exists,
+            multispace1,
+            context("view name", schema_table_reference_no_alias),
+            on_cluster,
+            opt(preceded(
+                tuple((multispace1, tag_no_case("to"), multispace1)),
+                context("table name", schema_table_reference_no_alias),
+            )),
+            view_field_list,
+            opt(preceded(
+                tuple((multispace1, tag_no_case("inner"), multispace1)),
+                engine_spec,
+            )),
+            opt(preceded(multispace1, engine_spec)),
+            opt(preceded(
+                tuple((multispace1, tag_no_case("watermark"), multispace0, tag("="), multispace0)),
+                context("watermark strategy", alt((
+                    map(tag_no_case("strictly_ascending"), |_| "STRICTLY_ASCENDING".to_string()),
+                    map(tag_no_case("ascending"), |_| "ASCENDING".to_string()),
+                    window_view_interval,
+                ))),
+            )),
+            opt(preceded(
+                tuple((multispace1, tag_no_case("allowed_lateness"), multispace0, tag("="), multispace0)),
+                context("interval", window_view_interval),
+            )),
+            populate,
+            as_select,
+        ))(i)?;
+
+    Ok((
+        remaining_input,
+        CreateWindowViewStatement {
+            view,
+            if_not_exists,
+            on_cluster,
+            to,
+            fields,
+            inner_engine,
+            engine,
+            watermark,
+            allowed_lateness,
+            populate,
+            select,
+        },
+    ))
+}
+
+// INTERVAL '2' SECOND
+fn window_view_interval(i: &[u8]) -> IResult<&[u8], String> {
+    map(interval_expression, |interval| interval.to_string())(i)
+}
+
+// Optional column list of a view, as printed by SHOW CREATE
+fn view_field_list(i: &[u8]) -> IResult<&[u8], Vec<ColumnSpecification>> {
+    map(
+        opt(preceded(
+            tuple((multispace0, tag("("), multispace0)),
+            cut(terminated(
+                field_specification_list,
+                tuple((multispace0, context("column definition or ')'", tag(")")))),
+            )),
+        )),
+        |fields| fields.unwrap_or_default()
+    )(i)
+}
+
+fn populate(i: &[u8]) -> IResult<&[u8], bool> {
+    map(opt(preceded(multispace1, tag_no_case("populate"))), |p| p.is_some())(i)
+}
+
+fn as_select(i: &[u8]) -> IResult<&[u8], SelectQuery> {
+    preceded(
+        tuple((multispace1, context("AS SELECT", tag_no_case("as")), multispace1)),
+        context("SELECT query", selection),
+    )(i)
+}
+
+pub fn or_replace(i: &[u8]) -> IResult<&[u8], bool> {
+    map(
+        opt(tuple((
+            multispace1,
+            tag_no_case("or"),
+            multispace1,
+            tag_no_case("replace"),
+        ))),
+        |o| o.is_some()
+    )(i)
+}
+
+pub fn database_creation(i: &[u8]) -> IResult<&[u8], CreateDatabaseStatement> {
+    // CREATE DATABASE [IF NOT EXISTS] db_name [ON CLUSTER cluster] [ENGINE = engine(...)] [COMMENT 'Comment']
+    let (remaining_input, (_, _, _, if_not_exists, _, name, on_cluster, engine, comment)) =
+        tuple((
+            tag_no_case("create"),
+            multispace1,
+            tag_no_case("database"),
+            if_not_exists,
+            multispace1,
+            context("database name", sql_identifier),
+            on_cluster,
+            opt(preceded(
+                tuple((multispace1, tag_no_case("engine"), multispace0, tag("="), multispace0)),
+                cut(context("database engine", database_engine)),
+            )),
+            opt(preceded(
+                tuple((multispace1, tag_no_case("comment"), multispace1)),
+                cut(context("comment", raw_string_single_quoted)),
+            )),
+        ))(i)?;
+
+    Ok((
+        remaining_input,
+        CreateDatabaseStatement {
+            name: str::from_utf8(name).unwrap().to_string(),
+            if_not_exists,
+            on_cluster,
+            engine,
+            comment: comment.map(|c| String::from_utf8(c).unwrap()),
         },
     ))
 }
 
+fn database_engine(i: &[u8]) -> IResult<&[u8], DatabaseEngine> {
+    let args_start = |i| tuple((multispace0, tag("("), multispace0))(i);
+    let args_end = |i| tuple((multispace0, tag(")")))(i);
+
+    alt((
+        map(tag_no_case("atomic"), |_| DatabaseEngine::Atomic),
+        map(tag_no_case("ordinary"), |_| DatabaseEngine::Ordinary),
+        map(
+            delimited(
+                tuple((tag_no_case("lazy"), args_start)),
+                map_res(digit1, |d| u64::from_str(str::from_utf8(d).unwrap())),
+                args_end,
+            ),
+            DatabaseEngine::Lazy
+        ),
+        map(
+            delimited(
+                tuple((tag_no_case("replicated"), args_start)),
+                tuple((
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                )),
+                args_end,
+            ),
+            |(zoo_path, _, shard, _, replica)| DatabaseEngine::Replicated { zoo_path, shard, replica }
+        ),
+        map(
+            delimited(
+                tuple((tag_no_case("mysql"), args_start)),
+                tuple((
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                )),
+                args_end,
+            ),
+            |(host, _, database, _, user, _, password)| DatabaseEngine::MySQL { host, database, user, password }
+        ),
+        map(
+            delimited(
+                tuple((tag_no_case("postgresql"), args_start)),
+                tuple((
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    ws_sep_comma,
+                    engine_string_argument,
+                    opt(tuple((
+                        ws_sep_comma,
+                        engine_string_argument,
+                        opt(preceded(ws_sep_comma, one_of("01"))),
+                    ))),
+                )),
+                args_end,
+            ),
+            |(host, _, database, _, user, _, password, opts)| {
+                let (schema, use_table_cache) = match opts {
+                    Some((_, schema, cache)) => (Some(schema), cache.map(|c| c == '1')),
+                    None => (None, None),
+                };
+                DatabaseEngine::PostgreSQL { host, database, user, password, schema, use_table_cache }
+            }
+        ),
+    ))(i)
+}
+
+// Engine argument given either as a string literal or as an identifier
+fn engine_string_argument(i: &[u8]) -> IResult<&[u8], String> {
+    alt((
+        map(raw_string_single_quoted, |s| String::from_utf8(s).unwrap()),
+        map(sql_identifier, |s| str::from_utf8(s).unwrap().to_string()),
+    ))(i)
+}
+
+pub fn if_not_exists(i: &[u8]) -> IResult<&[u8], bool> {
+    map(
+        opt(tuple((
+            multispace1,
+            tag_no_case("if"),
+            multispace1,
+            tag_no_case("not"),
+            multispace1,
+            tag_no_case("exists"),
+        ))),
+        |o| o.is_some()
+    )(i)
+}
+
+pub fn on_cluster(i: &[u8]) -> IResult<&[u8], Option<String>> {
+    opt(preceded(
+        tuple((
+            multispace1,
+            tag_no_case("on"),
+            multispace1,
+            tag_no_case("cluster"),
+            multispace1,
+        )),
+        context("cluster name", engine_string_argument),
+    ))(i)
+}
+
 fn engine_distributed(i: &[u8]) -> IResult<&[u8], Engine> {
     // Distributed(logs, default, hits[, sharding_key[, policy_name]])
     map(
@@ -301,17 +1197,17 @@ fn engine_distributed(i: &[u8]) -> IResult<&[u8], Engine> {
             multispace0,
             tag("("),
             multispace0,
-            sql_expression, // cluster
+            recognize(expression), // cluster
             ws_sep_comma,
             alt((
-                sql_expression, // schema
+                recognize(expression), // schema
                 map(tag("''"), |_| "".as_bytes()),
             )),
             ws_sep_comma,
-            sql_expression, // table
+            recognize(expression), // table
             opt(tuple((
                 ws_sep_comma,
-                sql_expression, // sharding_key
+                expression, // sharding_key
                 opt(tuple((
                     ws_sep_comma,
                     sql_identifier, // policy_name
@@ -322,12 +1218,9 @@ fn engine_distributed(i: &[u8]) -> IResult<&[u8], Engine> {
         )),
         |(_,_,_,_,cluster,_,schema,_,table,sharding_opts,_,_)| {
             let (sharding_key, policy_name) = match sharding_opts {
-                Some((_, key, None)) => (
-                    Some(str::from_utf8(key).unwrap().into()),
-                    None
-                ),
+                Some((_, key, None)) => (Some(key), None),
                 Some((_, key, Some((_, policy)))) => (
-                    Some(str::from_utf8(key).unwrap().into()),
+                    Some(key),
                     Some(str::from_utf8(policy).unwrap().into())
                 ),
                 _ => (None, None),
@@ -344,185 +1237,203 @@ fn engine_distributed(i: &[u8]) -> IResult<&[u8], Engine> {
     )(i)
 }
 
+#[derive(Clone, Copy)]
+enum MergeTreeFamily {
+    MergeTree,
+    Replacing,
+    Summing,
+    Aggregating,
+    Collapsing,
+    VersionedCollapsing,
+    Graphite,
+}
+
+fn merge_tree_family(i: &[u8]) -> IResult<&[u8], MergeTreeFamily> {
+    alt((
+        map(tag_no_case("VersionedCollapsingMergeTree"), |_| MergeTreeFamily::VersionedCollapsing),
+        map(tag_no_case("CollapsingMergeTree"), |_| MergeTreeFamily::Collapsing),
+        map(tag_no_case("ReplacingMergeTree"), |_| MergeTreeFamily::Replacing),
+        map(tag_no_case("SummingMergeTree"), |_| MergeTreeFamily::Summing),
+        map(tag_no_case("AggregatingMergeTree"), |_| MergeTreeFamily::Aggregating),
+        map(tag_no_case("GraphiteMergeTree"), |_| MergeTreeFamily::Graphite),
+        map(tag_no_case("MergeTree"), |_| MergeTreeFamily::MergeTree),
+    ))(i)
+}
+
+// Engine specific parameters following the replication ones
+fn merge_tree_params<'a>(family: MergeTreeFamily) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], MergeTreeKind> {
+    move |i| {
+        let column = |i| expression(i);
+        match family {
+            MergeTreeFamily::Replacing => map(
+                opt(pair(column, opt(preceded(ws_sep_comma, column)))),
+                |params| match params {
+                    Some((version, is_deleted)) => MergeTreeKind::Replacing { version: Some(version), is_deleted },
+                    None => MergeTreeKind::Replacing { version: None, is_deleted: None },
+                }
+            )(i),
+            MergeTreeFamily::Summing => map(
+                opt(alt((
+                    delimited(
+                        pair(tag("("), multispace0),
+                        separated_nonempty_list(ws_sep_comma, column),
+                        pair(multispace0, tag(")")),
+                    ),
+                    map(column, |c| vec![c]),
+                ))),
+                |columns| MergeTreeKind::Summing { columns: columns.unwrap_or_default() }
+            )(i),
+            MergeTreeFamily::Aggregating => Ok((i, MergeTreeKind::Aggregating)),
+            MergeTreeFamily::Collapsing => map(column, |sign| MergeTreeKind::Collapsing { sign })(i),
+            MergeTreeFamily::VersionedCollapsing => map(
+                tuple((column, ws_sep_comma, column)),
+                |(sign, _, version)| MergeTreeKind::VersionedCollapsing { sign, version }
+            )(i),
+            MergeTreeFamily::Graphite => map(
+                verify(literal, |l| matches!(l, Literal::String(_))),
+                |config_section| MergeTreeKind::Graphite { config_section }
+            )(i),
+            MergeTreeFamily::MergeTree => Ok((i, MergeTreeKind::MergeTree)),
+        }
+    }
+}
+
 fn engine_merge_tree(i: &[u8]) -> IResult<&[u8], Engine> {
-    // MergeTree PARTITION BY toYYYYMMDD(eventDate) PRIMARY KEY metric ORDER BY metric SETTINGS index_granularity = 8192
-    // ENGINE = MergeTree()
+    // [Replicated]{Replacing|Summing|...}MergeTree([zookeeper_path, replica_name, ]params...)
     // [PARTITION BY expr]
     // [ORDER BY expr]
     // [PRIMARY KEY expr]
     // [SAMPLE BY expr]
-    map(
-        recognize(tuple((
-            tag_no_case("MergeTree"),
-            many0(alt((
-                engine_merge_tree_partition,
-                engine_merge_tree_orderby,
-                engine_merge_tree_primary,
-                engine_merge_tree_sample,
-                engine_merge_tree_ttl,
-                engine_merge_tree_settings,
-            ))),
-        ))),
-        |s| {
-            Engine::MergeTree(EngineMergeTree(str::from_utf8(s).unwrap().to_string()))
-        }
-    )(i)
-}
-fn engine_merge_tree_partition(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        multispace1,
-        tag_no_case("PARTITION"),
-        multispace1,
-        tag_no_case("BY"),
-        multispace1,
-        sql_expression,
-    )))(i)
-}
-fn engine_merge_tree_orderby(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        multispace1,
-        tag_no_case("ORDER"),
-        multispace1,
-        tag_no_case("BY"),
-        multispace1,
-        sql_expression,
-    )))(i)
-}
-fn engine_merge_tree_primary(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        multispace1,
-        tag_no_case("PRIMARY"),
-        multispace1,
-        tag_no_case("BY"),
-        multispace1,
-        sql_expression,
-    )))(i)
-}
-fn engine_merge_tree_sample(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        multispace1,
-        tag_no_case("SAMPLE"),
-        multispace1,
-        tag_no_case("BY"),
-        multispace1,
-        sql_expression,
-    )))(i)
+    // [TTL ...]
+    // [SETTINGS name=value, ...]
+    let (i, replicated) = map(opt(tag_no_case("Replicated")), |r| r.is_some())(i)?;
+    let (i, family) = merge_tree_family(i)?;
+    let string = |i| map(raw_string_single_quoted, |s| String::from_utf8(s).unwrap())(i);
+    let arguments = |i| if replicated {
+        let (i, replication) = opt(map(
+            tuple((
+                string, // zookeeper path
+                ws_sep_comma,
+                string, // replica name
+            )),
+            |(path, _, replica)| (path, replica)
+        ))(i)?;
+        let (i, kind) = match replication {
+            Some(_) => alt((preceded(ws_sep_comma, merge_tree_params(family)), merge_tree_params(family)))(i)?,
+            None => merge_tree_params(family)(i)?,
+        };
+        Ok((i, (replication, kind)))
+    } else {
+        map(merge_tree_params(family), |kind| (None, kind))(i)
+    };
+    let (i, args) = opt(preceded(
+        tuple((multispace0, tag("("), multispace0)),
+        cut(context("engine parameters", terminated(arguments, pair(multispace0, tag(")"))))),
+    ))(i)?;
+    let (replication, kind) = match args {
+        Some(args) => args,
+        // `MergeTree`, `ReplacingMergeTree` etc. without parentheses
+        None => {
+            let (_, kind) = cut(context("engine parameters", merge_tree_params(family)))(&i[..0])?;
+            (None, kind)
+        },
+    };
+    let (i, clauses) = merge_tree_clauses(i)?;
+
+    Ok((i, if replicated {
+        let (zookeeper_path, replica_name) = match replication {
+            Some((path, replica)) => (Some(path), Some(replica)),
+            None => (None, None),
+        };
+        Engine::ReplicatedMergeTree(EngineReplicatedMergeTree { zookeeper_path, replica_name, kind, clauses })
+    } else {
+        Engine::MergeTree(EngineMergeTree { kind, clauses })
+    }))
 }
-fn engine_merge_tree_ttl(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    // TTL [expr [DELETE|TO DISK 'xxx'|TO VOLUME 'xxx']], ...
-    recognize(tuple((
-        multispace0,
-        tag_no_case("TTL"),
-        multispace0,
-        separated_list(ws_sep_comma, tuple((
-            ttl_expression,
-            multispace0,
-            opt(tuple((
-                alt((
-                    tag_no_case("delete"),
-                    tag_no_case("delete"),
-                    recognize(tuple((
-                        tag_no_case("to"),
-                        multispace1,
-                        tag_no_case("disk"),
-                        multispace1,
-                        delimited(tag("'"), take_until("'"), tag("'")),
-                    ))),
-                    recognize(tuple((
-                        tag_no_case("to"),
-                        multispace1,
-                        tag_no_case("volume"),
-                        multispace1,
-                        delimited(tag("'"), take_until("'"), tag("'")),
-                    ))),
-                )),
-                multispace0,
-            ))),
-        ))),
-    )))(i)
+
+enum MergeTreeClause {
+    PartitionBy(Expr),
+    OrderBy(Expr),
+    PrimaryKey(Expr),
+    SampleBy(Expr),
+    Ttl(Vec<TtlRule>),
+    Settings(Vec<(String, Expr)>),
 }
-fn engine_merge_tree_settings(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    // [SETTINGS name=value, ...]
-    recognize(tuple((
-        multispace0,
-        tag_no_case("SETTINGS"),
-        multispace0,
-        separated_list(ws_sep_comma, engine_merge_tree_settings_kv)
-    )))(i)
-}
-fn engine_merge_tree_settings_kv(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    let numeric = recognize(tuple((
-        alt((
-            tag_no_case("index_granularity"),
-            tag_no_case("index_granularity_bytes"),
-            tag_no_case("enable_mixed_granularity_parts"),
-            tag_no_case("use_minimalistic_part_header_in_zookeeper"),
-            tag_no_case("min_merge_bytes_to_use_direct_io"),
-            tag_no_case("merge_with_ttl_timeout"),
-            tag_no_case("write_final_mark"),
-            tag_no_case("merge_max_block_size"),
-            tag_no_case("min_bytes_for_wide_part"),
-            tag_no_case("min_rows_for_wide_part"),
-        )),
-        multispace0,
-        tag("="),
-        multispace0,
-        digit1,
-    )));
 
-    let string = recognize(tuple((
-        //alt((
-            tag_no_case("storage_policy"),
-        //)),
-        multispace0,
-        tag("="),
-        multispace0,
-        delimited(tag("'"), take_until("'"), tag("'")),
-    )));
+// Clauses may come in any order, but each of them only once
+fn merge_tree_clauses(i: &[u8]) -> IResult<&[u8], MergeTreeClauses> {
+    let key = |first: &'static str, second: &'static str| preceded(
+        tuple((multispace0, tag_no_case(first), multispace1, tag_no_case(second), multispace1)),
+        cut(context("expression", expression)),
+    );
+    let clause = alt((
+        map(key("PARTITION", "BY"), MergeTreeClause::PartitionBy),
+        map(key("ORDER", "BY"), MergeTreeClause::OrderBy),
+        map(key("PRIMARY", "KEY"), MergeTreeClause::PrimaryKey),
+        map(key("SAMPLE", "BY"), MergeTreeClause::SampleBy),
+        map(engine_merge_tree_ttl, MergeTreeClause::Ttl),
+        map(engine_merge_tree_settings, MergeTreeClause::Settings),
+    ));
+    let mut clauses = MergeTreeClauses::default();
+    let mut i = i;
+    loop {
+        let (rest, clause) = match clause(i) {
+            Ok(res) => res,
+            Err(NomErr::Error(_)) => return Ok((i, clauses)),
+            Err(e) => return Err(e),
+        };
+        let repeated = match clause {
+            MergeTreeClause::PartitionBy(expr) => clauses.partition_by.replace(expr).is_some(),
+            MergeTreeClause::OrderBy(expr) => clauses.order_by.replace(expr).is_some(),
+            MergeTreeClause::PrimaryKey(expr) => clauses.primary_key.replace(expr).is_some(),
+            MergeTreeClause::SampleBy(expr) => clauses.sample_by.replace(expr).is_some(),
+            MergeTreeClause::Ttl(rules) => !std::mem::replace(&mut clauses.ttl, rules).is_empty(),
+            MergeTreeClause::Settings(settings) => !std::mem::replace(&mut clauses.settings, settings).is_empty(),
+        };
+        if repeated {
+            let (position, _) = multispace0(i)?;
+            return Err(NomErr::Failure(SyntaxError {
+                input: position,
+                kind: ErrorKind::Verify,
+                expected: Some("unique MergeTree clause"),
+            }));
+        }
+        i = rest;
+    }
+}
 
+fn ttl_action(i: &[u8]) -> IResult<&[u8], TtlAction> {
+    let target = |kind: &'static str| preceded(
+        tuple((tag_no_case("to"), multispace1, tag_no_case(kind), multispace1)),
+        map(raw_string_single_quoted, |s| String::from_utf8(s).unwrap()),
+    );
     alt((
-        numeric,
-        string,
+        map(tag_no_case("delete"), |_| TtlAction::Delete),
+        map(target("disk"), TtlAction::ToDisk),
+        map(target("volume"), TtlAction::ToVolume),
     ))(i)
 }
 
-fn engine_replicated_merge_tree(i: &[u8]) -> IResult<&[u8], Engine> {
-    //  ENGINE = ReplicatedReplacingMergeTree('/clickhouse/tables/{layer}-{shard}/table_name', '{replica}', ver)
-    //  PARTITION BY toYYYYMM(EventDate)
-    //  ORDER BY (CounterID, EventDate, intHash32(UserID))
-    //  SAMPLE BY intHash32(UserID)
+pub fn ttl_rule(i: &[u8]) -> IResult<&[u8], TtlRule> {
     map(
-        recognize(tuple((
-            tag_no_case("ReplicatedMergeTree"),
-            multispace0,
-            tag("("),
-            multispace0,
-            sql_expression, // zookeeper path
-            multispace0,
-            tag(","),
-            multispace0,
-            sql_expression, // replica name
-            opt(tuple((
-                multispace0,
-                tag(","),
-                multispace0,
-                sql_expression, // engine params (FIXME: not for all Replicated*MergeTree engines?
-            ))),
-            multispace0,
-            tag(")"),
-            multispace0,
-            many0(alt((
-                engine_merge_tree_partition,
-                engine_merge_tree_orderby,
-                engine_merge_tree_primary,
-                engine_merge_tree_sample,
-                engine_merge_tree_ttl,
-                engine_merge_tree_settings,
-            ))),
-        ))),
-        |s| {
-            Engine::ReplicatedMergeTree(EngineReplicatedMergeTree(str::from_utf8(s).unwrap().to_string()))
-        }
+        pair(expression, opt(preceded(multispace1, ttl_action))),
+        |(expr, action)| TtlRule { expr, action }
+    )(i)
+}
+
+fn engine_merge_tree_ttl(i: &[u8]) -> IResult<&[u8], Vec<TtlRule>> {
+    // TTL [expr [DELETE|TO DISK 'xxx'|TO VOLUME 'xxx']], ...
+    preceded(
+        tuple((multispace0, tag_no_case("TTL"), multispace1)),
+        cut(context("TTL rule", separated_nonempty_list(ws_sep_comma, ttl_rule))),
+    )(i)
+}
+
+fn engine_merge_tree_settings(i: &[u8]) -> IResult<&[u8], Vec<(String, Expr)>> {
+    // [SETTINGS name=value, ...]
+    preceded(
+        tuple((multispace0, tag_no_case("SETTINGS"), multispace1)),
+        cut(context("setting", separated_nonempty_list(ws_sep_comma, setting))),
     )(i)
 }
 
@@ -535,7 +1446,6 @@ fn engine(i: &[u8]) -> IResult<&[u8], Engine> {
        engine_distributed, 
        engine_memory,
        engine_merge_tree,
-       engine_replicated_merge_tree,
     ))(i)
 }
 
@@ -546,46 +1456,24 @@ fn engine_spec(i: &[u8]) -> IResult<&[u8], Engine> {
             multispace0,
             tag("="),
             multispace0,
-            engine,
+            cut(context("table engine", engine)),
         )),
         |(_, _, _, _, engine)| engine
     )(i)
 }
 
-pub fn field_specification_opts(i: &[u8]) -> IResult<&[u8], SqlTypeOpts> {
-    alt((
-        map(delimited(
-            tag_no_case("Nullable("),
-            type_identifier,
-            tag(")"),
-        ), |ftype| SqlTypeOpts{ftype, nullable: true, lowcardinality:false}),
-        map(delimited(
-            tag_no_case("LowCardinality("),
-            type_identifier,
-            tag(")"),
-        ), |ftype| SqlTypeOpts{ftype, nullable: false, lowcardinality:true}),
-        map(delimited(
-            tag_no_case("LowCardinality(Nullable("),
-            type_identifier,
-            tag("))"),
-        ), |ftype| SqlTypeOpts{ftype, nullable: true, lowcardinality:true}),
-        map(type_identifier,
-            |ftype| SqlTypeOpts{ftype, nullable: false, lowcardinality: false}),
-    ))(i)
-}
-
 pub fn field_specification(i: &[u8]) -> IResult<&[u8], ColumnSpecification> {
     let (remaining_input, (column, field_type, option, comment, codec, ttl)) = tuple((
         column_identifier_no_alias,
         delimited(
             multispace1,
-            field_specification_opts,
+            cut(context("column type", type_identifier)),
             multispace0),
-        alt((
-            opt(column_default),
-            opt(column_materialized),
+        opt(alt((
+            column_default,
+            column_materialized,
             //column_alias,
-        )),
+        ))),
         opt(preceded(multispace0, column_comment)),
         opt(preceded(multispace0, column_codec_list)),
         opt(preceded(multispace0, column_ttl)),
@@ -595,13 +1483,11 @@ pub fn field_specification(i: &[u8]) -> IResult<&[u8], ColumnSpecification> {
         remaining_input,
         ColumnSpecification {
             column,
-            sql_type: field_type.ftype,
+            sql_type: field_type,
             codec,
             ttl,
-            nullable: field_type.nullable,
             option,
             comment,
-            lowcardinality: field_type.lowcardinality,
         },
     ))
 }
@@ -609,10 +1495,10 @@ pub fn column_comment(i: &[u8]) -> IResult<&[u8], String> {
     let (remaining_input, (_, _, comment)) = tuple((
         tag_no_case("COMMENT"),
         multispace0,
-        delimited(tag("'"), take_until("'"), tag("'")),
+        raw_string_single_quoted,
     ))(i)?;
 
-    Ok((remaining_input, str::from_utf8(comment).unwrap().to_string() ))
+    Ok((remaining_input, String::from_utf8(comment).unwrap()))
 }
 
 // Parse rule for a comma-separated list.
@@ -625,11 +1511,11 @@ pub fn column_codec_list(i: &[u8]) -> IResult<&[u8], CodecList> {
     let (remaining_input, (_, _, list)) = tuple((
         tag_no_case("codec"),
         multispace0,
-        delimited(
+        cut(context("codec list", delimited(
             delimited(multispace0, tag("("), multispace0),
             separated_list(ws_sep_comma, column_codec),
             delimited(multispace0, tag(")"), multispace0),
-        ),
+        ))),
     ))(i)?;
 
     Ok((remaining_input, CodecList(list)))
@@ -654,45 +1540,29 @@ pub fn column_codec(i: &[u8]) -> IResult<&[u8], Codec> {
             None => Codec::Delta(None),
         },
     );
+    let level = |expected: &'static str, levels: std::ops::RangeInclusive<u8>| cut(context(
+        expected,
+        verify(map_res(digit1, |l| str::from_utf8(l).unwrap().parse::<u8>()), move |l| levels.contains(l)),
+    ));
     let zstd = map(
         preceded(
             tag_no_case("zstd"), opt( delimited(
                     delimited(multispace0, tag("("), multispace0),
-                    digit1,
+                    level("ZSTD level from 1 to 22", 1..=22),
                     delimited(multispace0, tag(")"), multispace0),
             )),
         ),
-        |s: Option<&[u8]>| match s {
-            Some(l) => {
-                let l = u8::from_str(str::from_utf8(l).unwrap()).unwrap();
-                if l >= 1 && l <= 22 {
-                    Codec::ZSTD(Some(l)) // FIXME process error
-                }else{
-                    panic!("Unsupported level '{}' for codec ZSTD", l)
-                }
-            },
-            None => Codec::ZSTD(None),
-        },
+        Codec::ZSTD,
     );
     let lz4hc = map(
         preceded(
             tag_no_case("LZ4HC"), opt( delimited(
                     delimited(multispace0, tag("("), multispace0),
-                    digit1,
+                    level("LZ4HC level from 0 to 12", 0..=12),
                     delimited(multispace0, tag(")"), multispace0),
             )),
         ),
-        |s: Option<&[u8]>| match s {
-            Some(l) => {
-                let l = u8::from_str(str::from_utf8(l).unwrap()).unwrap();
-                if l <= 12 {
-                    Codec::LZ4HC(Some(l)) // FIXME process error
-                }else{
-                    panic!("Unsupported level '{}' for codec LZ4HC", l)
-                }
-            },
-            None => Codec::LZ4HC(None),
-        },
+        Codec::LZ4HC,
     );
     alt((
         none,
@@ -707,19 +1577,10 @@ pub fn column_codec(i: &[u8]) -> IResult<&[u8], Codec> {
 }
 
 pub fn column_ttl(i: &[u8]) -> IResult<&[u8], ColumnTTL> {
-    let ttl = map(
-        tuple((multispace0, tag_no_case("TTL"), multispace0, sql_identifier, multispace0)),
-        |(_, _, _, name, _)| ColumnTTL::from(str::from_utf8(name).unwrap()),
-    );
-    let ttl_interval = map(
-        tuple((multispace0, tag_no_case("TTL-FIXME"), multispace0, sql_identifier, multispace0)),
-        |(_, _, _, name, _)| ColumnTTL::from(str::from_utf8(name).unwrap()),
-    );
-
-    alt((
-            ttl,
-            ttl_interval,
-    ))(i)
+    map(
+        tuple((multispace0, tag_no_case("TTL"), multispace1, expression, multispace0)),
+        |(_, _, _, expr, _)| ColumnTTL { expr },
+    )(i)
 }
 
 fn column_default(i: &[u8]) -> IResult<&[u8], ColumnOption> {
@@ -727,27 +1588,23 @@ fn column_default(i: &[u8]) -> IResult<&[u8], ColumnOption> {
         multispace0,
         tag_no_case("default"),
         multispace1,
-        sql_expression,
+        cut(context("DEFAULT expression", expression)),
         multispace0,
     ))(i)?;
 
-    Ok((remaining_input, ColumnOption::DefaultValue(
-        str::from_utf8(def).unwrap().to_string()
-    )))
+    Ok((remaining_input, ColumnOption::DefaultValue(def)))
 }
 
 fn column_materialized(i: &[u8]) -> IResult<&[u8], ColumnOption> {
     let (remaining_input, (_, _, _, def, _)) = tuple((
         multispace0,
-        tag_no_case("default"),
+        tag_no_case("materialized"),
         multispace1,
-        sql_expression,
+        cut(context("MATERIALIZED expression", expression)),
         multispace0,
     ))(i)?;
 
-    Ok((remaining_input, ColumnOption::Materialized(
-        str::from_utf8(def).unwrap().to_string()
-    )))
+    Ok((remaining_input, ColumnOption::Materialized(def)))
 }
 
 #[cfg(test)]
@@ -761,7 +1618,7 @@ mod test {
         let res = column_ttl(string.as_bytes());
         assert_eq!(
             res.unwrap().1,
-            ColumnTTL::from("time_column"),
+            ColumnTTL { expr: "time_column".into() },
         );
     }
 
@@ -784,6 +1641,22 @@ mod test {
         parse_set_for_test(column_codec, patterns);
     }
 
+    #[test]
+    fn t_column_codec_error() {
+        let source = "CODEC(Delta(4), ZSTD(99))";
+        let err = crate::ParseError::from_nom(source.as_bytes(), column_codec_list(source.as_bytes()).unwrap_err());
+        assert_eq!(err.expected, "ZSTD level from 1 to 22");
+        assert_eq!((err.column, err.found.as_str()), (22, "`99`"));
+
+        let source = "CODEC(LZ4HC(300))";
+        let err = crate::ParseError::from_nom(source.as_bytes(), column_codec_list(source.as_bytes()).unwrap_err());
+        assert_eq!(err.expected, "LZ4HC level from 0 to 12");
+        assert_eq!(err.column, 13);
+
+        let err = crate::parse_query("CREATE TABLE t (`a` UInt8 CODEC(ZSTD(0))) ENGINE = Memory").unwrap_err();
+        assert_eq!(err.expected, "ZSTD level from 1 to 22");
+    }
+
     #[test]
     fn t_column_codec_list() {
         let patterns = vec![
@@ -807,11 +1680,18 @@ mod test {
     #[test]
     fn t_field_spec() {
         let patterns = vec![
-            ( "LowCardinality(Nullable(String))", SqlTypeOpts{ftype: SqlType::String, nullable: true, lowcardinality: true,} ),
-            ( "Nullable(String)", SqlTypeOpts{ftype: SqlType::String, nullable: true, lowcardinality: false,} ),
-            ( "Int8", SqlTypeOpts{ftype: SqlType::Int(TypeSize::B8), nullable: false, lowcardinality: false,} ),
+            (
+                "LowCardinality(Nullable(String))",
+                SqlType::LowCardinality(Box::new(SqlType::Nullable(Box::new(SqlType::String))))
+            ),
+            ( "Nullable(String)", SqlType::Nullable(Box::new(SqlType::String)) ),
+            ( "Int8", SqlType::Int(TypeSize::B8) ),
+            (
+                "Array(Nullable(String))",
+                SqlType::Array(Box::new(SqlType::Nullable(Box::new(SqlType::String))))
+            ),
         ];
-        parse_set_for_test(field_specification_opts, patterns);
+        parse_set_for_test(type_identifier, patterns);
     }
     
     #[test]
@@ -821,8 +1701,18 @@ mod test {
             ( "Nullable(String)", "Nullable(String)".to_string()),
             ( "LowCardinality(String)", "LowCardinality(String)".to_string()),
             ( "LowCardinality(Nullable(String))", "LowCardinality(Nullable(String))".to_string()),
+            ( "Array(LowCardinality(String))", "Array(LowCardinality(String))".to_string()),
+            ( "nullable( Float64 )", "Nullable(Float64)".to_string()),
+            (
+                "Map(LowCardinality(String), Nullable(Float64))",
+                "Map(LowCardinality(String), Nullable(Float64))".to_string()
+            ),
+            (
+                "Tuple(a Nullable(UInt8), b Array(LowCardinality(Nullable(String))))",
+                "Tuple(a Nullable(UInt8), b Array(LowCardinality(Nullable(String))))".to_string()
+            ),
         ];
-        parse_set_for_test(|i| field_specification_opts(i)
+        parse_set_for_test(|i| type_identifier(i)
                 .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
             patterns);
     }
@@ -837,7 +1727,7 @@ mod test {
                     cluster_name: "'cluster1'".into(),
                     schema: "'schema1'".into(),
                     table: "'table1'".into(),
-                    sharding_key: Some("rand()".into()),
+                    sharding_key: Some(Expr::Function(Function { name: "rand".into(), params: None, distinct: false, args: vec![] })),
                     policy_name: None,
                 })
             ),
@@ -847,25 +1737,398 @@ mod test {
                     cluster_name: "'cluster1'".into(),
                     schema: "''".into(),
                     table: "'table1'".into(),
-                    sharding_key: Some("rand()".into()),
+                    sharding_key: Some(Expr::Function(Function { name: "rand".into(), params: None, distinct: false, args: vec![] })),
                     policy_name: None,
                 })
             ),
+            (
+                "MergeTree() PARTITION BY toYYYYMM(d) ORDER BY (a, b) SETTINGS index_granularity = 8192",
+                Engine::MergeTree(EngineMergeTree {
+                    kind: MergeTreeKind::MergeTree,
+                    clauses: MergeTreeClauses {
+                        partition_by: Some(expression(b"toYYYYMM(d)").unwrap().1),
+                        order_by: Some(Expr::Tuple(vec!["a".into(), "b".into()])),
+                        settings: vec![("index_granularity".into(), Expr::Literal(Literal::UnsignedInteger(8192)))],
+                        ..Default::default()
+                    },
+                })
+            ),
+            (
+                "ReplicatedMergeTree('/clickhouse/tables/{shard}/t', '{replica}') ORDER BY tuple()",
+                Engine::ReplicatedMergeTree(EngineReplicatedMergeTree {
+                    zookeeper_path: Some("/clickhouse/tables/{shard}/t".into()),
+                    replica_name: Some("{replica}".into()),
+                    kind: MergeTreeKind::MergeTree,
+                    clauses: MergeTreeClauses {
+                        order_by: Some(Expr::Function(Function { name: "tuple".into(), params: None, distinct: false, args: vec![] })),
+                        ..Default::default()
+                    },
+                })
+            ),
+            (
+                "ReplicatedVersionedCollapsingMergeTree('/t', 'r', sign, ver) ORDER BY id",
+                Engine::ReplicatedMergeTree(EngineReplicatedMergeTree {
+                    zookeeper_path: Some("/t".into()),
+                    replica_name: Some("r".into()),
+                    kind: MergeTreeKind::VersionedCollapsing { sign: "sign".into(), version: "ver".into() },
+                    clauses: MergeTreeClauses {
+                        order_by: Some("id".into()),
+                        ..Default::default()
+                    },
+                })
+            ),            (
+                "ReplacingMergeTree(ver, is_deleted) ORDER BY id SETTINGS storage_policy = 'ssd', merge_with_ttl_timeout = 3600",
+                Engine::MergeTree(EngineMergeTree {
+                    kind: MergeTreeKind::Replacing { version: Some("ver".into()), is_deleted: Some("is_deleted".into()) },
+                    clauses: MergeTreeClauses {
+                        order_by: Some("id".into()),
+                        settings: vec![
+                            ("storage_policy".into(), Expr::Literal(Literal::String("ssd".into()))),
+                            ("merge_with_ttl_timeout".into(), Expr::Literal(Literal::UnsignedInteger(3600))),
+                        ],
+                        ..Default::default()
+                    },
+                })
+            ),
+            (
+                "SummingMergeTree((a, b))",
+                Engine::MergeTree(EngineMergeTree {
+                    kind: MergeTreeKind::Summing { columns: vec!["a".into(), "b".into()] },
+                    clauses: MergeTreeClauses::default(),
+                })
+            ),
+            (
+                "GraphiteMergeTree('graphite_rollup')",
+                Engine::MergeTree(EngineMergeTree {
+                    kind: MergeTreeKind::Graphite { config_section: Literal::String("graphite_rollup".into()) },
+                    clauses: MergeTreeClauses::default(),
+                })
+            ),
+            (
+                "ReplicatedReplacingMergeTree ORDER BY id",
+                Engine::ReplicatedMergeTree(EngineReplicatedMergeTree {
+                    zookeeper_path: None,
+                    replica_name: None,
+                    kind: MergeTreeKind::Replacing { version: None, is_deleted: None },
+                    clauses: MergeTreeClauses {
+                        order_by: Some("id".into()),
+                        ..Default::default()
+                    },
+                })
+            ),
         ];
         parse_set_for_test(engine, patterns);
     }
 
+    #[test]
+    fn t_engine_display() {
+        let patterns = vec![
+            ( "MergeTree", "ENGINE = MergeTree".to_string() ),
+            ( "MergeTree()", "ENGINE = MergeTree".to_string() ),
+            (
+                "MergeTree\nORDER BY metric\nPRIMARY KEY metric\nPARTITION BY toYYYYMMDD(eventDate)\nSETTINGS index_granularity=8192",
+                "ENGINE = MergeTree PARTITION BY toYYYYMMDD(eventDate) PRIMARY KEY metric ORDER BY metric SETTINGS index_granularity = 8192".to_string()
+            ),
+            (
+                "MergeTree ORDER BY (metric,eventDate) SAMPLE BY intHash32(UserID) \nTTL a + INTERVAL 1 MONTH,\n   b + interval 1 day to volume 'aaa' SETTINGS storage_policy = 'ssd'",
+                "ENGINE = MergeTree ORDER BY (metric, eventDate) SAMPLE BY intHash32(UserID) TTL a + INTERVAL 1 MONTH, b + INTERVAL 1 DAY TO VOLUME 'aaa' SETTINGS storage_policy = 'ssd'".to_string()
+            ),
+            (
+                "ReplicatedMergeTree('/clickhouse/tables/t', 'replica-1') PARTITION BY d ORDER BY d",
+                "ENGINE = ReplicatedMergeTree('/clickhouse/tables/t', 'replica-1') PARTITION BY d ORDER BY d".to_string()
+            ),
+            ( "ReplacingMergeTree", "ENGINE = ReplacingMergeTree".to_string() ),
+            ( "ReplacingMergeTree(ver) ORDER BY id", "ENGINE = ReplacingMergeTree(ver) ORDER BY id".to_string() ),
+            ( "ReplacingMergeTree(ver, is_deleted)", "ENGINE = ReplacingMergeTree(ver, is_deleted)".to_string() ),
+            ( "SummingMergeTree()", "ENGINE = SummingMergeTree".to_string() ),
+            ( "SummingMergeTree(hits)", "ENGINE = SummingMergeTree(hits)".to_string() ),
+            ( "SummingMergeTree(( a,b ))", "ENGINE = SummingMergeTree((a, b))".to_string() ),
+            ( "AggregatingMergeTree ORDER BY (d, key)", "ENGINE = AggregatingMergeTree ORDER BY (d, key)".to_string() ),
+            ( "CollapsingMergeTree(Sign) ORDER BY id", "ENGINE = CollapsingMergeTree(Sign) ORDER BY id".to_string() ),
+            ( "VersionedCollapsingMergeTree(Sign, Version)", "ENGINE = VersionedCollapsingMergeTree(Sign, Version)".to_string() ),
+            ( "GraphiteMergeTree('graphite_rollup')", "ENGINE = GraphiteMergeTree('graphite_rollup')".to_string() ),
+            (
+                "ReplicatedReplacingMergeTree('/clickhouse/tables/{layer}-{shard}/t', '{replica}', ver) ORDER BY id",
+                "ENGINE = ReplicatedReplacingMergeTree('/clickhouse/tables/{layer}-{shard}/t', '{replica}', ver) ORDER BY id".to_string()
+            ),
+            (
+                "ReplicatedReplacingMergeTree('/t', '{replica}')",
+                "ENGINE = ReplicatedReplacingMergeTree('/t', '{replica}')".to_string()
+            ),
+            (
+                "ReplicatedSummingMergeTree('/t', 'r', (a, b))",
+                "ENGINE = ReplicatedSummingMergeTree('/t', 'r', (a, b))".to_string()
+            ),
+            ( "ReplicatedAggregatingMergeTree('/t', 'r')", "ENGINE = ReplicatedAggregatingMergeTree('/t', 'r')".to_string() ),
+            ( "ReplicatedMergeTree ORDER BY id", "ENGINE = ReplicatedMergeTree ORDER BY id".to_string() ),
+            ( "ReplicatedMergeTree()", "ENGINE = ReplicatedMergeTree".to_string() ),
+            ( "ReplicatedReplacingMergeTree(ver)", "ENGINE = ReplicatedReplacingMergeTree(ver)".to_string() ),
+            ( "ReplicatedCollapsingMergeTree(sign)", "ENGINE = ReplicatedCollapsingMergeTree(sign)".to_string() ),
+            ( "ReplicatedCollapsingMergeTree('/t', 'r', sign)", "ENGINE = ReplicatedCollapsingMergeTree('/t', 'r', sign)".to_string() ),
+            (
+                "ReplicatedVersionedCollapsingMergeTree('/t', 'r', sign, ver)",
+                "ENGINE = ReplicatedVersionedCollapsingMergeTree('/t', 'r', sign, ver)".to_string()
+            ),
+            (
+                "ReplicatedGraphiteMergeTree('/t', 'r', 'graphite_rollup')",
+                "ENGINE = ReplicatedGraphiteMergeTree('/t', 'r', 'graphite_rollup')".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| engine(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+    }
+
+    fn engine_error(engine_spec: &str) -> crate::ParseError {
+        crate::ParseError::from_nom(engine_spec.as_bytes(), engine(engine_spec.as_bytes()).unwrap_err())
+    }
+
+    #[test]
+    fn t_engine_error() {
+        let err = engine_error("MergeTree ORDER BY a PARTITION BY d ORDER BY b");
+        assert_eq!(err.expected, "unique MergeTree clause");
+        assert_eq!((err.column, err.found.as_str()), (37, "`ORDER`"));
+        assert_eq!(engine_error("MergeTree TTL d + 1 SETTINGS a = 1 TTL d + 2").expected, "unique MergeTree clause");
+        assert_eq!(engine_error("MergeTree SETTINGS a = 1 SETTINGS b = 2").expected, "unique MergeTree clause");
+
+        assert_eq!(engine_error("CollapsingMergeTree").expected, "engine parameters");
+        assert_eq!(engine_error("VersionedCollapsingMergeTree(sign)").expected, "engine parameters");
+
+        let err = engine_error("ReplicatedCollapsingMergeTree('/t', 'r')");
+        assert_eq!(err.expected, "engine parameters");
+        assert_eq!(err.found, "`)`");
+    }
+
+    #[test]
+    fn t_ttl_rule() {
+        let patterns = vec![
+            ( "d", "d".to_string() ),
+            ( "d + INTERVAL 1 MONTH DELETE", "d + INTERVAL 1 MONTH DELETE".to_string() ),
+            ( "d + toIntervalDay(14) TO DISK 'cold'", "d + toIntervalDay(14) TO DISK 'cold'".to_string() ),
+            ( "d - interval 15 year to volume 'v'", "d - INTERVAL 15 YEAR TO VOLUME 'v'".to_string() ),
+        ];
+        parse_set_for_test(|i| ttl_rule(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+    }
+
+    #[test]
+    fn t_materialized_view() {
+        let patterns = vec![
+            (
+                "CREATE MATERIALIZED VIEW db.mv TO db.t AS SELECT a, count() FROM db.src GROUP BY a",
+                "CREATE MATERIALIZED VIEW db.mv TO db.t AS SELECT a, count() FROM db.src GROUP BY a;".to_string()
+            ),
+            (
+                "create materialized view if not exists mv on cluster '{cluster}' engine = Memory populate as select 1;",
+                "CREATE MATERIALIZED VIEW IF NOT EXISTS mv ON CLUSTER '{cluster}' ENGINE = Memory POPULATE AS SELECT 1;".to_string()
+            ),
+            (
+                "CREATE MATERIALIZED VIEW db.mv (`a` String) ENGINE = MergeTree ORDER BY a AS SELECT 'x;y' AS a",
+                "CREATE MATERIALIZED VIEW db.mv (\n  `a` String\n) ENGINE = MergeTree ORDER BY a AS SELECT 'x;y' AS a;".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| materialized_view_creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+
+        let (_, mv) = materialized_view_creation(
+            "CREATE MATERIALIZED VIEW db.mv ON CLUSTER main TO db.t AS SELECT * FROM db.src".as_bytes()
+        ).unwrap();
+        assert_eq!(mv.on_cluster, Some("main".into()));
+        assert_eq!(mv.to, Some(Table::from(("db", "t"))));
+        assert_eq!(mv.engine, None);
+        assert_eq!(mv.select.to_string(), "SELECT * FROM db.src");
+
+        assert!(matches!(
+            parse_query("CREATE MATERIALIZED VIEW mv TO t AS SELECT 1;"),
+            Ok(SqlQuery::CreateMaterializedView(_))
+        ));
+
+        for q in &[
+            "CREATE MATERIALIZED VIEW mv TO dst ENGINE = Memory POPULATE AS SELECT 1",
+            "CREATE MATERIALIZED VIEW mv TO dst ENGINE = Memory AS SELECT 1",
+            "CREATE MATERIALIZED VIEW mv TO dst POPULATE AS SELECT 1",
+        ] {
+            let err = parse_query(q).unwrap_err();
+            assert_eq!(err.expected, "AS SELECT", "{}", q);
+            assert_eq!(err.column, 36, "{}", q);
+        }
+    }
+
+    #[test]
+    fn t_view() {
+        let patterns = vec![
+            (
+                "CREATE VIEW db.v AS SELECT 1",
+                "CREATE VIEW db.v AS SELECT 1;".to_string()
+            ),
+            (
+                "create or replace view if not exists v on cluster c (`a` UInt8) as with 1 as x select x as a",
+                "CREATE OR REPLACE VIEW IF NOT EXISTS v ON CLUSTER c (\n  `a` UInt8\n) AS WITH 1 AS x SELECT x AS a;".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| view_creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+    }
+
+    #[test]
+    fn t_live_view() {
+        let patterns = vec![
+            (
+                "CREATE LIVE VIEW lv AS SELECT sum(x) FROM t",
+                "CREATE LIVE VIEW lv AS SELECT sum(x) FROM t;".to_string()
+            ),
+            (
+                "CREATE LIVE VIEW lv WITH REFRESH 5 AS SELECT now()",
+                "CREATE LIVE VIEW lv WITH REFRESH 5 AS SELECT now();".to_string()
+            ),
+            (
+                "CREATE LIVE VIEW lv with timeout and refresh AS SELECT now()",
+                "CREATE LIVE VIEW lv WITH TIMEOUT AND REFRESH AS SELECT now();".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| live_view_creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+    }
+
+    #[test]
+    fn t_window_view() {
+        let patterns = vec![
+            (
+                "CREATE WINDOW VIEW wv TO dst WATERMARK=ASCENDING AS SELECT count(id) FROM src GROUP BY tumble(ts, INTERVAL '10' SECOND)",
+                "CREATE WINDOW VIEW wv TO dst WATERMARK = ASCENDING AS SELECT count(id) FROM src GROUP BY tumble(ts, INTERVAL '10' SECOND);".to_string()
+            ),
+            (
+                "CREATE WINDOW VIEW wv INNER ENGINE = Memory ENGINE = Memory WATERMARK = INTERVAL '2' SECOND ALLOWED_LATENESS = INTERVAL 1 SECOND POPULATE AS SELECT 1",
+                "CREATE WINDOW VIEW wv INNER ENGINE = Memory ENGINE = Memory WATERMARK = INTERVAL '2' SECOND ALLOWED_LATENESS = INTERVAL 1 SECOND POPULATE AS SELECT 1;".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| window_view_creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+
+        let script = "CREATE VIEW v AS SELECT 1; CREATE LIVE VIEW lv AS SELECT 1; CREATE WINDOW VIEW wv AS SELECT 1;";
+        let queries = parse_statements(script);
+        assert!(matches!(queries[0], Ok(SqlQuery::CreateView(_))));
+        assert!(matches!(queries[1], Ok(SqlQuery::CreateLiveView(_))));
+        assert!(matches!(queries[2], Ok(SqlQuery::CreateWindowView(_))));
+    }
+
+    #[test]
+    fn t_table_header() {
+        let patterns = vec![
+            (
+                "CREATE TABLE IF NOT EXISTS db.t ON CLUSTER '{cluster}' (`a` UInt8) ENGINE = Memory",
+                "CREATE TABLE IF NOT EXISTS db.t ON CLUSTER '{cluster}' (\n  `a` UInt8\n) ENGINE = Memory;".to_string()
+            ),
+            (
+                "create temporary table t (`a` UInt8) engine = Memory",
+                "CREATE TEMPORARY TABLE t (\n  `a` UInt8\n) ENGINE = Memory;".to_string()
+            ),
+            (
+                "CREATE TEMPORARY TABLE t (`a` UInt8)",
+                "CREATE TEMPORARY TABLE t (\n  `a` UInt8\n);".to_string()
+            ),
+            (
+                "CREATE OR REPLACE TABLE t ON CLUSTER main (`a` UInt8) ENGINE = Memory",
+                "CREATE OR REPLACE TABLE t ON CLUSTER main (\n  `a` UInt8\n) ENGINE = Memory;".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+
+        let (_, t) = creation("CREATE TABLE IF NOT EXISTS t ON CLUSTER c (`a` UInt8) ENGINE = Memory".as_bytes()).unwrap();
+        assert!(t.if_not_exists && !t.or_replace && !t.temporary);
+        assert_eq!(t.on_cluster, Some("c".into()));
+
+        let (_, t) = creation("CREATE TEMPORARY TABLE t (`a` UInt8)".as_bytes()).unwrap();
+        assert!(t.temporary);
+        assert_eq!(t.engine, None);
+    }
+
+    #[test]
+    fn t_table_source() {
+        let patterns = vec![
+            (
+                "CREATE TABLE t2 AS db.t ENGINE = Memory",
+                "CREATE TABLE t2 AS db.t ENGINE = Memory;".to_string()
+            ),
+            (
+                "CREATE TABLE t2 ON CLUSTER c AS db.t",
+                "CREATE TABLE t2 ON CLUSTER c AS db.t;".to_string()
+            ),
+            (
+                "CREATE TABLE t2 AS remote('host', db, t)",
+                "CREATE TABLE t2 AS remote('host', db, t);".to_string()
+            ),
+            (
+                "CREATE TABLE t2 ENGINE = Memory AS SELECT * FROM t",
+                "CREATE TABLE t2 ENGINE = Memory AS SELECT * FROM t;".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+
+        let (_, t) = creation("CREATE TABLE t2 AS db.t".as_bytes()).unwrap();
+        assert_eq!(t.source, CreateTableSource::Table(Table::from(("db", "t"))));
+        assert_eq!(t.engine, None);
+
+        let (_, t) = creation("CREATE TABLE t2 AS remote('host', db, t)".as_bytes()).unwrap();
+        assert_eq!(t.source, CreateTableSource::TableFunction(Function {
+            name: "remote".into(),
+            params: None,
+            distinct: false,
+            args: vec![Expr::Literal(Literal::String("host".into())), "db".into(), "t".into()],
+        }));
+    }
+
+    #[test]
+    fn t_database() {
+        let patterns = vec![
+            ( "CREATE DATABASE db", "CREATE DATABASE db;".to_string() ),
+            (
+                "create database if not exists `my-db` on cluster '{cluster}' engine = Atomic comment 'it\\'s mine'",
+                "CREATE DATABASE IF NOT EXISTS `my-db` ON CLUSTER '{cluster}' ENGINE = Atomic COMMENT 'it\\'s mine';".to_string()
+            ),
+            ( "CREATE DATABASE db ENGINE = Lazy(3600)", "CREATE DATABASE db ENGINE = Lazy(3600);".to_string() ),
+            (
+                "CREATE DATABASE db ENGINE = Replicated('/clickhouse/databases/db', '{shard}', '{replica}')",
+                "CREATE DATABASE db ENGINE = Replicated('/clickhouse/databases/db', '{shard}', '{replica}');".to_string()
+            ),
+            (
+                "CREATE DATABASE db ENGINE = MySQL('localhost:3306', test, 'user', 'pass')",
+                "CREATE DATABASE db ENGINE = MySQL('localhost:3306', 'test', 'user', 'pass');".to_string()
+            ),
+            (
+                "CREATE DATABASE db ENGINE = PostgreSQL('localhost:5432', 'test', 'user', 'pass', 'public', 1)",
+                "CREATE DATABASE db ENGINE = PostgreSQL('localhost:5432', 'test', 'user', 'pass', 'public', 1);".to_string()
+            ),
+        ];
+        parse_set_for_test(|i| database_creation(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns);
+
+        let (_, db) = database_creation("CREATE DATABASE db ENGINE = Lazy(10)".as_bytes()).unwrap();
+        assert_eq!(db.engine, Some(DatabaseEngine::Lazy(10)));
+
+        let err = parse_query("CREATE DATABASE db ENGINE = Unknown").unwrap_err();
+        assert_eq!(err.expected, "database engine");
+    }
+
     #[test]
     fn t_column_display_codec_ttl_nullable() {
         let cs = ColumnSpecification {
             column: "time_local".into(),
-            sql_type: SqlType::DateTime(None),
+            sql_type: SqlType::Nullable(Box::new(SqlType::DateTime(None))),
             codec: Some(CodecList(vec![ Codec::Delta(Some(CodecDeltaLevel::L1)), Codec::LZ4, Codec::ZSTD(None) ])),
-            ttl: Some(ColumnTTL::from(("1", "2"))),
-            nullable: true,
+            ttl: Some(ColumnTTL { expr: expression(b"1 + 2").unwrap().1 }),
             option: None,
             comment: None,
-            lowcardinality: false,
         };
 
         let exp = "`time_local` Nullable(DateTime) CODEC(Delta(1), LZ4, ZSTD) TTL 1 + 2";
@@ -879,11 +2142,9 @@ mod test {
             column: "time_local".into(),
             sql_type: SqlType::DateTime(None),
             codec: Some(CodecList(vec![ Codec::Delta(Some(CodecDeltaLevel::L1)), Codec::LZ4, Codec::ZSTD(None) ])),
-            ttl: Some(ColumnTTL::from(("1", "2"))),
-            nullable: false,
+            ttl: Some(ColumnTTL { expr: expression(b"1 + 2").unwrap().1 }),
             option: None,
             comment: None,
-            lowcardinality: false,
         };
 
         let exp = "`time_local` DateTime CODEC(Delta(1), LZ4, ZSTD) TTL 1 + 2";
@@ -897,6 +2158,10 @@ mod test {
                 "`reg` UInt32 DEFAULT CAST(0, 'UInt32') COMMENT 'комментарий' CODEC(Delta(4))",
                 "`reg` UInt32 DEFAULT CAST(0, 'UInt32') COMMENT 'комментарий' CODEC(Delta(4))".to_string()
             ),
+            (
+                r"`id` UInt64 COMMENT 'user\'s id, see ''docs'''",
+                r"`id` UInt64 COMMENT 'user\'s id, see \'docs\''".to_string()
+            ),
             (
                 "`reg` UInt32 CODEC(Delta(4))",
                 "`reg` UInt32 CODEC(Delta(4))".to_string()
@@ -905,6 +2170,30 @@ mod test {
                 "`reg` UInt32 CODEC(Delta(4))",
                 "`reg` UInt32 CODEC(Delta(4))".to_string()
             ),
+            (
+                "`day` Date MATERIALIZED toDate(ts)  TTL day+1",
+                "`day` Date MATERIALIZED toDate(ts) TTL day + 1".to_string()
+            ),
+            (
+                "`tags_lower` String MATERIALIZED arrayMap(x->lower(x), tags)",
+                "`tags_lower` String MATERIALIZED arrayMap(x -> lower(x), tags)".to_string()
+            ),
+            (
+                "`amount` Nullable(Decimal64(4)) DEFAULT 0",
+                "`amount` Nullable(Decimal(18, 4)) DEFAULT 0".to_string()
+            ),
+            (
+                "`attrs` Map(String,UInt64) CODEC(ZSTD(1))",
+                "`attrs` Map(String, UInt64) CODEC(ZSTD(1))".to_string()
+            ),
+            (
+                "`first_tag` String DEFAULT tags[1]",
+                "`first_tag` String DEFAULT tags[1]".to_string()
+            ),
+            (
+                "`host` String DEFAULT attrs['host'].1",
+                "`host` String DEFAULT attrs['host'].1".to_string()
+            ),
         ];
         parse_set_for_test(|i| field_specification(i)
                 .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
@@ -0,0 +1,257 @@
+// vim: set expandtab ts=4 sw=4:
+
+use std::fmt;
+use std::error::Error;
+
+use crate::is_sql_identifier;
+use nom::{
+    Err as NomErr,
+    InputLength,
+    Offset,
+    error::{ErrorKind, ParseError as NomParseError},
+};
+
+/// Error type of the nom parsers in this crate.
+///
+/// Unlike nom's default `(I, ErrorKind)` it keeps the failure which got
+/// furthest into the input when `alt` branches are combined, together with the
+/// innermost `context` label describing what was expected there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError<I> {
+    pub input: I,
+    pub kind: ErrorKind,
+    pub expected: Option<&'static str>,
+}
+
+impl<I: InputLength> NomParseError<I> for SyntaxError<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        SyntaxError {
+            input,
+            kind,
+            expected: None,
+        }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(self, other: Self) -> Self {
+        let (left, right) = (self.input.input_len(), other.input.input_len());
+        if right < left || (right == left && self.expected.is_none()) {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn add_context(_: I, ctx: &'static str, mut other: Self) -> Self {
+        if other.expected.is_none() {
+            other.expected = Some(ctx);
+        }
+        other
+    }
+}
+
+/// Position and description of a query which failed to parse
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ParseError {
+    /// Byte offset from the start of the input
+    pub offset: usize,
+    /// Line number, starting from 1
+    pub line: usize,
+    /// Column number in characters, starting from 1
+    pub column: usize,
+    /// Construct expected at this position, e.g. "column type"
+    pub expected: String,
+    /// Token found at this position instead
+    pub found: String,
+    /// Source line containing the failure
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Builds an error for `position`, which must be a suffix of `source`.
+    pub fn new(source: &[u8], position: &[u8], expected: &str) -> ParseError {
+        let offset = source.offset(position);
+        let line_start = source[..offset]
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |p| p + 1);
+        let line_end = source[offset..]
+            .iter()
+            .position(|&c| c == b'\n')
+            .map_or(source.len(), |p| offset + p);
+        let line = source[..offset].iter().filter(|&&c| c == b'\n').count() + 1;
+        let column = String::from_utf8_lossy(&source[line_start..offset]).chars().count() + 1;
+        let snippet = String::from_utf8_lossy(&source[line_start..line_end])
+            .trim_end_matches('\r')
+            .to_string();
+
+        ParseError {
+            offset,
+            line,
+            column,
+            expected: expected.to_string(),
+            found: found_token(&source[offset..]),
+            snippet,
+        }
+    }
+
+    /// Converts a parser failure on `source` into a positional error.
+    pub fn from_nom(source: &[u8], err: NomErr<SyntaxError<&[u8]>>) -> ParseError {
+        match err {
+            NomErr::Incomplete(_) => ParseError::new(source, &source[source.len()..], "more input"),
+            NomErr::Error(e) | NomErr::Failure(e) => {
+                let expected = e.expected.unwrap_or_else(|| describe_kind(e.kind));
+                ParseError::new(source, e.input, expected)
+            },
+        }
+    }
+
+    /// Renders a compiler-style report: the message, the location prefixed
+    /// with `origin` (e.g. a file name), the failing line and a caret marker
+    /// under the failing token.
+    pub fn render(&self, origin: &str) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        // keep tabs so that the marker lines up with the source line
+        let indent: String = self.snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        // `found` is either a backquoted token or a description like "end of input"
+        let width = if self.found.starts_with('`') {
+            self.found.chars().count() - 2
+        } else {
+            1
+        };
+
+        format!(
+            "error: expected {}, found {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.expected, self.found,
+            gutter, origin, self.line, self.column,
+            gutter,
+            number, self.snippet,
+            gutter, indent, "^".repeat(width),
+        )
+    }
+}
+
+/// Describes the token at the start of `rest` for error messages
+fn found_token(rest: &[u8]) -> String {
+    let len = match rest.first() {
+        None => return "end of input".to_string(),
+        Some(b'\n') | Some(b'\r') => return "end of line".to_string(),
+        Some(&q) if q == b'\'' || q == b'"' || q == b'`' => rest[1..]
+            .iter()
+            .position(|&c| c == q || c == b'\n')
+            .map_or(rest.len(), |p| p + 2),
+        Some(_) => rest
+            .iter()
+            .position(|&c| !is_sql_identifier(c))
+            .unwrap_or(rest.len())
+            .max(1),
+    };
+    let token = String::from_utf8_lossy(&rest[..len.min(rest.len())]);
+    // a multibyte character split by a single-byte token
+    let token = token.trim_end_matches('\u{fffd}');
+    if token.is_empty() {
+        String::from_utf8_lossy(rest).chars().take(1).map(|c| format!("`{}`", c)).collect()
+    } else {
+        format!("`{}`", token)
+    }
+}
+
+fn describe_kind(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Eof => "end of input",
+        ErrorKind::Digit => "number",
+        ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::TakeWhile1 => "identifier",
+        _ => "valid syntax",
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, found {} at line {}, column {}: {}",
+            self.expected,
+            self.found,
+            self.line,
+            self.column,
+            self.snippet.trim()
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use crate::parse_query;
+
+    #[test]
+    fn t_parse_error_position() {
+        let source = "CREATE TABLE t (\n  `a` UInt8,\n  `b` Strang\n) ENGINE = Memory";
+        let err = parse_query(source).unwrap_err();
+        assert_eq!(err.offset, source.find("Strang").unwrap());
+        assert_eq!(err.line, 3);
+        assert_eq!(err.column, 7);
+        assert_eq!(err.expected, "column type");
+        assert_eq!(err.found, "`Strang`");
+        assert_eq!(err.snippet, "  `b` Strang");
+    }
+
+    #[test]
+    fn t_parse_error_engine() {
+        let source = "CREATE TABLE t (`a` UInt8) ENGINE = Unknown";
+        let err = parse_query(source).unwrap_err();
+        assert_eq!(err.column, 37);
+        assert_eq!(err.expected, "table engine");
+
+        let err = parse_query("CREATE TABLE t (`a` UInt8)").unwrap_err();
+        assert_eq!(err.expected, "ENGINE clause");
+    }
+
+    #[test]
+    fn t_parse_error_trailing_input() {
+        for (q, found) in &[
+            ("SELECT a FROM t WHERE", "`WHERE`"),
+            ("SELECT a FROM t; DROP TABLE x", "`DROP`"),
+            ("CREATE TABLE t (`a` UInt8) ENGINE = MergeTree PARTITON BY a ORDER BY a", "`PARTITON`"),
+            ("CREATE TABLE t (`a` UInt8) ENGINE = Memory; DROP TABLE x", "`DROP`"),
+        ] {
+            let err = parse_query(q).unwrap_err();
+            assert_eq!(err.expected, "end of statement");
+            assert_eq!(&err.found, found);
+        }
+        assert!(parse_query("SELECT a FROM t; -- done\n").is_ok());
+    }
+
+    #[test]
+    fn t_parse_error_leading_whitespace() {
+        let err = parse_query("\n\n  CRATE TABLE t").unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.expected, "statement");
+    }
+
+    #[test]
+    fn t_parse_error_render() {
+        let source = "CREATE TABLE t (\n\t`a` UInt8,\n\t`b` Strang\n) ENGINE = Memory";
+        let exp = concat!(
+            "error: expected column type, found `Strang`\n",
+            " --> schema.sql:3:6\n",
+            "  |\n",
+            "3 | \t`b` Strang\n",
+            "  | \t    ^^^^^^\n",
+        );
+        assert_eq!(exp, parse_query(source).unwrap_err().render("schema.sql"));
+
+        let err = parse_query("CREATE TABLE t (`a` UInt8").unwrap_err();
+        assert_eq!(err.found, "end of input");
+        assert!(err.render("-").ends_with("1 | CREATE TABLE t (`a` UInt8\n  |                          ^\n"));
+    }
+}
@@ -0,0 +1,1294 @@
+// vim: set expandtab ts=4 sw=4:
+use std::str;
+use std::fmt;
+
+use nom::{
+    Err as NomErr,
+    branch::alt,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    combinator::{map, map_res, not, opt, recognize, verify},
+    character::complete::{digit1, hex_digit1, oct_digit1},
+    bytes::complete::{tag, tag_no_case, take_while1},
+    multi::{fold_many0, separated_list, separated_nonempty_list},
+};
+
+use crate::{
+    IResult,
+    multispace0,
+    multispace1,
+    SqlType,
+    column_identifier_no_alias,
+    escape_identifier,
+    escape_string,
+    is_sql_identifier,
+    raw_string_single_quoted,
+    sql_identifier,
+    type_identifier,
+    ws_sep_comma,
+};
+use crate::column::Column;
+use crate::select::{SelectQuery, nested_selection};
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Literal {
+    Null,
+    Boolean(bool),
+    // negative integers, `-1`
+    Integer(i64),
+    // decimal, hex `0x1F`, binary `0b101` or octal `0o17` integers
+    UnsignedInteger(u64),
+    // kept as written (`1.5e-3`, `-inf`, `nan`), f64 is neither Eq nor Hash
+    Float(String),
+    String(String),
+    Array(Vec<Literal>),
+    Map(Vec<(Literal, Literal)>),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::Null => write!(f, "NULL"),
+            Literal::Boolean(b) => write!(f, "{}", b),
+            Literal::Integer(n) => write!(f, "{}", n),
+            Literal::UnsignedInteger(n) => write!(f, "{}", n),
+            Literal::Float(n) => write!(f, "{}", n),
+            Literal::String(s) => write!(f, "{}", escape_string(s)),
+            Literal::Array(items) => write!(f, "[{}]",
+                items
+                    .iter()
+                    .map(|l| format!("{}", l))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Literal::Map(items) => write!(f, "{{{}}}",
+                items
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Function {
+    pub name: String,
+    // parameters of parametric aggregate functions, e.g. quantile(0.9)(x)
+    pub params: Option<Vec<Expr>>,
+    // count(DISTINCT x)
+    pub distinct: bool,
+    pub args: Vec<Expr>,
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(ref params) = self.params {
+            write!(f, "({})", expression_list(params))?;
+        }
+        if self.distinct {
+            write!(f, "(DISTINCT {})", expression_list(&self.args))
+        } else {
+            write!(f, "({})", expression_list(&self.args))
+        }
+    }
+}
+
+// Units of INTERVAL and EXTRACT
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum IntervalUnit {
+    Nanosecond,
+    Microsecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl fmt::Display for IntervalUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntervalUnit::Nanosecond => write!(f, "NANOSECOND"),
+            IntervalUnit::Microsecond => write!(f, "MICROSECOND"),
+            IntervalUnit::Millisecond => write!(f, "MILLISECOND"),
+            IntervalUnit::Second => write!(f, "SECOND"),
+            IntervalUnit::Minute => write!(f, "MINUTE"),
+            IntervalUnit::Hour => write!(f, "HOUR"),
+            IntervalUnit::Day => write!(f, "DAY"),
+            IntervalUnit::Week => write!(f, "WEEK"),
+            IntervalUnit::Month => write!(f, "MONTH"),
+            IntervalUnit::Quarter => write!(f, "QUARTER"),
+            IntervalUnit::Year => write!(f, "YEAR"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum UnaryOperator {
+    Minus,
+    Not,
+}
+
+impl UnaryOperator {
+    fn precedence(&self) -> u8 {
+        match *self {
+            UnaryOperator::Minus => 100,
+            UnaryOperator::Not => 50,
+        }
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnaryOperator::Minus => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "NOT "),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BinaryOperator {
+    Multiply,
+    Divide,
+    Modulo,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Like,
+    NotLike,
+    ILike,
+    NotILike,
+    In,
+    NotIn,
+    GlobalIn,
+    GlobalNotIn,
+    Concat,
+    And,
+    Or,
+}
+
+impl BinaryOperator {
+    fn is_in(&self) -> bool {
+        matches!(self,
+            BinaryOperator::In | BinaryOperator::NotIn | BinaryOperator::GlobalIn | BinaryOperator::GlobalNotIn
+        )
+    }
+
+    fn precedence(&self) -> u8 {
+        match *self {
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 90,
+            BinaryOperator::Plus | BinaryOperator::Minus => 80,
+            BinaryOperator::Concat => 70,
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Less
+            | BinaryOperator::Greater
+            | BinaryOperator::LessOrEqual
+            | BinaryOperator::GreaterOrEqual
+            | BinaryOperator::Like
+            | BinaryOperator::NotLike
+            | BinaryOperator::ILike
+            | BinaryOperator::NotILike
+            | BinaryOperator::In
+            | BinaryOperator::NotIn
+            | BinaryOperator::GlobalIn
+            | BinaryOperator::GlobalNotIn => 60,
+            BinaryOperator::And => 40,
+            BinaryOperator::Or => 30,
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BinaryOperator::Multiply => write!(f, "*"),
+            BinaryOperator::Divide => write!(f, "/"),
+            BinaryOperator::Modulo => write!(f, "%"),
+            BinaryOperator::Plus => write!(f, "+"),
+            BinaryOperator::Minus => write!(f, "-"),
+            BinaryOperator::Equal => write!(f, "="),
+            BinaryOperator::NotEqual => write!(f, "!="),
+            BinaryOperator::Less => write!(f, "<"),
+            BinaryOperator::Greater => write!(f, ">"),
+            BinaryOperator::LessOrEqual => write!(f, "<="),
+            BinaryOperator::GreaterOrEqual => write!(f, ">="),
+            BinaryOperator::Like => write!(f, "LIKE"),
+            BinaryOperator::NotLike => write!(f, "NOT LIKE"),
+            BinaryOperator::ILike => write!(f, "ILIKE"),
+            BinaryOperator::NotILike => write!(f, "NOT ILIKE"),
+            BinaryOperator::In => write!(f, "IN"),
+            BinaryOperator::NotIn => write!(f, "NOT IN"),
+            BinaryOperator::GlobalIn => write!(f, "GLOBAL IN"),
+            BinaryOperator::GlobalNotIn => write!(f, "GLOBAL NOT IN"),
+            BinaryOperator::Concat => write!(f, "||"),
+            BinaryOperator::And => write!(f, "AND"),
+            BinaryOperator::Or => write!(f, "OR"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Expr {
+    Literal(Literal),
+    Column(Column),
+    // [a, b + 1], arrays of constants are literals
+    Array(Vec<Expr>),
+    // `*` argument, e.g. count(*)
+    Asterisk,
+    Function(Function),
+    Unary {
+        op: UnaryOperator,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOperator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    // expr [NOT] BETWEEN low AND high
+    Between {
+        expr: Box<Expr>,
+        negated: bool,
+        low: Box<Expr>,
+        high: Box<Expr>,
+    },
+    // expr IS [NOT] NULL
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    // condition ? then : else
+    Ternary {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    // (SELECT ...), e.g. the right side of IN
+    Subquery(Box<SelectQuery>),
+    // CAST(expr AS type), CAST(expr, 'type') is a plain function call
+    Cast {
+        expr: Box<Expr>,
+        sql_type: SqlType,
+    },
+    Tuple(Vec<Expr>),
+    // CASE [operand] WHEN .. THEN .. [ELSE ..] END
+    Case {
+        operand: Option<Box<Expr>>,
+        conditions: Vec<(Expr, Expr)>,
+        else_result: Option<Box<Expr>>,
+    },
+    // INTERVAL 1 DAY
+    Interval {
+        value: Box<Expr>,
+        unit: IntervalUnit,
+    },
+    // EXTRACT(DAY FROM d)
+    Extract {
+        unit: IntervalUnit,
+        expr: Box<Expr>,
+    },
+    // SUBSTRING(s FROM 1 [FOR 2])
+    Substring {
+        expr: Box<Expr>,
+        from: Box<Expr>,
+        length: Option<Box<Expr>>,
+    },
+    // arr[1], map['key']
+    Subscript {
+        expr: Box<Expr>,
+        index: Box<Expr>,
+    },
+    // tup.1
+    TupleElement {
+        expr: Box<Expr>,
+        index: u64,
+    },
+    // tup.name, t.nested.field
+    Member {
+        expr: Box<Expr>,
+        name: String,
+    },
+    // x -> expr, (x, y) -> expr
+    Lambda {
+        params: Vec<String>,
+        body: Box<Expr>,
+    },
+}
+
+impl Expr {
+    // Binding strength, operands binding weaker than their operator are
+    // printed in parentheses
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Lambda { .. } => 10,
+            Expr::Ternary { .. } => 20,
+            Expr::IsNull { .. } => 55,
+            Expr::Between { .. } => 57,
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Unary { op, .. } => op.precedence(),
+            Expr::Subscript { .. } | Expr::TupleElement { .. } | Expr::Member { .. } => 110,
+            _ => 120,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::Column(column) => write!(f, "{}", column),
+            Expr::Array(items) => write!(f, "[{}]", expression_list(items)),
+            Expr::Asterisk => write!(f, "*"),
+            Expr::Function(function) => write!(f, "{}", function),
+            Expr::Unary { op: UnaryOperator::Minus, expr } => {
+                let operand = if expr.precedence() <= self.precedence() {
+                    format!("({})", expr)
+                } else {
+                    expr.to_string()
+                };
+                // `-1` and `-inf` would be read back as negative literals, `--1` as a comment
+                if matches!(**expr, Expr::Literal(Literal::Float(_)))
+                    || operand.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+                {
+                    write!(f, "-({})", operand)
+                } else {
+                    write!(f, "-{}", operand)
+                }
+            },
+            Expr::Unary { op, expr } => {
+                write!(f, "{}", op)?;
+                expr.fmt_operand(f, self.precedence())
+            },
+            Expr::Binary { op, left, right } => {
+                // all binary operators are left-associative
+                left.fmt_operand(f, op.precedence())?;
+                write!(f, " {} ", op)?;
+                match **right {
+                    Expr::Tuple(ref items) if op.is_in() && items.len() == 1 => write!(f, "({})", items[0]),
+                    ref right => right.fmt_operand(f, op.precedence() + 1),
+                }
+            },
+            Expr::Between { expr, negated, low, high } => {
+                expr.fmt_operand(f, self.precedence() + 1)?;
+                write!(f, " {}BETWEEN ", if *negated { "NOT " } else { "" })?;
+                low.fmt_operand(f, 60)?;
+                write!(f, " AND ")?;
+                high.fmt_operand(f, 60)
+            },
+            Expr::IsNull { expr, negated } => {
+                expr.fmt_operand(f, self.precedence() + 1)?;
+                write!(f, " IS {}NULL", if *negated { "NOT " } else { "" })
+            },
+            Expr::Ternary { condition, then, otherwise } => {
+                // right-associative: a ? b : c ? d : e
+                condition.fmt_operand(f, self.precedence() + 1)?;
+                write!(f, " ? ")?;
+                then.fmt_operand(f, self.precedence() + 1)?;
+                write!(f, " : ")?;
+                otherwise.fmt_operand(f, self.precedence())
+            },
+            Expr::Subquery(query) => write!(f, "({})", query),
+            Expr::Cast { expr, sql_type } => write!(f, "CAST({} AS {})", expr, sql_type),
+            Expr::Tuple(items) if items.len() == 1 => write!(f, "tuple({})", items[0]),
+            Expr::Tuple(items) => write!(f, "({})", expression_list(items)),
+            Expr::Case { operand, conditions, else_result } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (condition, result) in conditions {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            },
+            Expr::Interval { value, unit } => {
+                write!(f, "INTERVAL ")?;
+                value.fmt_operand(f, 100)?;
+                write!(f, " {}", unit)
+            },
+            Expr::Extract { unit, expr } => write!(f, "EXTRACT({} FROM {})", unit, expr),
+            Expr::Substring { expr, from, length } => {
+                write!(f, "SUBSTRING({} FROM {}", expr, from)?;
+                if let Some(length) = length {
+                    write!(f, " FOR {}", length)?;
+                }
+                write!(f, ")")
+            },
+            Expr::Subscript { expr, index } => {
+                expr.fmt_operand(f, self.precedence())?;
+                write!(f, "[{}]", index)
+            },
+            Expr::TupleElement { expr, index } => {
+                expr.fmt_operand(f, self.precedence())?;
+                write!(f, ".{}", index)
+            },
+            Expr::Member { expr, name } => {
+                expr.fmt_operand(f, self.precedence())?;
+                write!(f, ".{}", escape_identifier(name))
+            },
+            Expr::Lambda { params, body } => {
+                let params = params.iter().map(|p| escape_identifier(p)).collect::<Vec<String>>();
+                if params.len() == 1 {
+                    write!(f, "{} -> {}", params[0], body)
+                } else {
+                    write!(f, "({}) -> {}", params.join(", "), body)
+                }
+            },
+        }
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(c: &str) -> Expr {
+        Expr::Column(c.into())
+    }
+}
+
+fn expression_list(items: &[Expr]) -> String {
+    items
+        .iter()
+        .map(|e| format!("{}", e))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Folds `operand (operator operand)*` into left-associative binary expressions
+fn left_assoc<'a, O, P>(i: &'a [u8], operand: O, operator: P) -> IResult<&'a [u8], Expr>
+where
+    O: Fn(&'a [u8]) -> IResult<&'a [u8], Expr>,
+    P: Fn(&'a [u8]) -> IResult<&'a [u8], BinaryOperator>,
+{
+    let (mut i, mut left) = operand(i)?;
+    loop {
+        match pair(delimited(multispace0, &operator, multispace0), &operand)(i) {
+            Ok((rest, (op, right))) => {
+                left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
+                i = rest;
+            },
+            Err(NomErr::Error(_)) => return Ok((i, left)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Case-insensitive keyword which is not a prefix of a longer identifier
+fn keyword<'a>(word: &'static str) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
+    terminated(tag_no_case(word), not(take_while1(is_sql_identifier)))
+}
+
+fn keyword_operator(i: &[u8]) -> IResult<&[u8], BinaryOperator> {
+    alt((
+        map(keyword("like"), |_| BinaryOperator::Like),
+        map(keyword("ilike"), |_| BinaryOperator::ILike),
+        map(keyword("in"), |_| BinaryOperator::In),
+        map(tuple((keyword("not"), multispace1, keyword("like"))), |_| BinaryOperator::NotLike),
+        map(tuple((keyword("not"), multispace1, keyword("ilike"))), |_| BinaryOperator::NotILike),
+        map(tuple((keyword("not"), multispace1, keyword("in"))), |_| BinaryOperator::NotIn),
+        map(tuple((keyword("global"), multispace1, keyword("in"))), |_| BinaryOperator::GlobalIn),
+        map(
+            tuple((keyword("global"), multispace1, keyword("not"), multispace1, keyword("in"))),
+            |_| BinaryOperator::GlobalNotIn
+        ),
+    ))(i)
+}
+
+fn comparison_operator(i: &[u8]) -> IResult<&[u8], BinaryOperator> {
+    alt((
+        keyword_operator,
+        map(tag("<="), |_| BinaryOperator::LessOrEqual),
+        map(tag(">="), |_| BinaryOperator::GreaterOrEqual),
+        map(tag("<>"), |_| BinaryOperator::NotEqual),
+        map(tag("!="), |_| BinaryOperator::NotEqual),
+        map(tag("=="), |_| BinaryOperator::Equal),
+        map(tag("<"), |_| BinaryOperator::Less),
+        map(tag(">"), |_| BinaryOperator::Greater),
+        map(tag("="), |_| BinaryOperator::Equal),
+    ))(i)
+}
+
+// `-` which does not start a `--` comment
+fn minus(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    terminated(tag("-"), not(tag("-")))(i)
+}
+
+fn additive_operator(i: &[u8]) -> IResult<&[u8], BinaryOperator> {
+    alt((
+        map(tag("+"), |_| BinaryOperator::Plus),
+        map(minus, |_| BinaryOperator::Minus),
+    ))(i)
+}
+
+fn multiplicative_operator(i: &[u8]) -> IResult<&[u8], BinaryOperator> {
+    alt((
+        map(tag("*"), |_| BinaryOperator::Multiply),
+        map(tag("/"), |_| BinaryOperator::Divide),
+        map(tag("%"), |_| BinaryOperator::Modulo),
+    ))(i)
+}
+
+// Precedence levels from the loosest to the tightest binding:
+// ->, ?:, OR, AND, NOT, IS NULL, BETWEEN, comparisons, ||, + -, * / %, unary -
+pub fn expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    alt((lambda_expression, ternary_expression))(i)
+}
+
+fn lambda_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    let param = |i| map(sql_identifier, |p| str::from_utf8(p).unwrap().to_string())(i);
+    map(
+        tuple((
+            alt((
+                map(param, |p| vec![p]),
+                delimited(
+                    pair(tag("("), multispace0),
+                    separated_nonempty_list(ws_sep_comma, param),
+                    pair(multispace0, tag(")")),
+                ),
+            )),
+            delimited(multispace0, tag("->"), multispace0),
+            expression,
+        )),
+        |(params, _, body)| Expr::Lambda { params, body: Box::new(body) }
+    )(i)
+}
+
+fn ternary_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        pair(
+            or_expression,
+            opt(tuple((
+                delimited(multispace0, tag("?"), multispace0),
+                ternary_expression,
+                delimited(multispace0, tag(":"), multispace0),
+                ternary_expression,
+            ))),
+        ),
+        |(condition, branches)| match branches {
+            Some((_, then, _, otherwise)) => Expr::Ternary {
+                condition: Box::new(condition),
+                then: Box::new(then),
+                otherwise: Box::new(otherwise),
+            },
+            None => condition,
+        }
+    )(i)
+}
+
+fn or_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    left_assoc(i, and_expression, map(keyword("or"), |_| BinaryOperator::Or))
+}
+
+fn and_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    left_assoc(i, not_expression, map(keyword("and"), |_| BinaryOperator::And))
+}
+
+fn not_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    alt((
+        map(
+            preceded(pair(keyword("not"), multispace0), not_expression),
+            |expr| Expr::Unary { op: UnaryOperator::Not, expr: Box::new(expr) }
+        ),
+        is_null_expression,
+    ))(i)
+}
+
+fn is_null_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        pair(
+            between_expression,
+            opt(tuple((
+                multispace1,
+                keyword("is"),
+                multispace1,
+                opt(terminated(keyword("not"), multispace1)),
+                keyword("null"),
+            ))),
+        ),
+        |(expr, is_null)| match is_null {
+            Some((_, _, _, not, _)) => Expr::IsNull { expr: Box::new(expr), negated: not.is_some() },
+            None => expr,
+        }
+    )(i)
+}
+
+fn between_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        pair(
+            comparison_expression,
+            opt(tuple((
+                multispace1,
+                opt(terminated(keyword("not"), multispace1)),
+                keyword("between"),
+                multispace1,
+                comparison_expression,
+                delimited(multispace1, keyword("and"), multispace1),
+                comparison_expression,
+            ))),
+        ),
+        |(expr, between)| match between {
+            Some((_, not, _, _, low, _, high)) => Expr::Between {
+                expr: Box::new(expr),
+                negated: not.is_some(),
+                low: Box::new(low),
+                high: Box::new(high),
+            },
+            None => expr,
+        }
+    )(i)
+}
+
+fn comparison_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    let (mut i, mut left) = concat_expression(i)?;
+    loop {
+        let (rest, op) = match delimited(multispace0, comparison_operator, multispace0)(i) {
+            Ok(res) => res,
+            Err(NomErr::Error(_)) => return Ok((i, left)),
+            Err(e) => return Err(e),
+        };
+        let right = if op.is_in() {
+            alt((in_tuple, concat_expression))(rest)
+        } else {
+            concat_expression(rest)
+        };
+        match right {
+            Ok((rest, right)) => {
+                left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
+                i = rest;
+            },
+            Err(NomErr::Error(_)) => return Ok((i, left)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// `x IN ((1, 2))` is a set of one tuple, unlike `x IN (1, 2)`
+fn in_tuple(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        verify(
+            delimited(pair(tag("("), multispace0), expression, pair(multispace0, tag(")"))),
+            |expr| matches!(expr, Expr::Tuple(_)),
+        ),
+        |tuple| Expr::Tuple(vec![tuple])
+    )(i)
+}
+
+fn concat_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    left_assoc(i, additive_expression, map(tag("||"), |_| BinaryOperator::Concat))
+}
+
+fn additive_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    left_assoc(i, multiplicative_expression, additive_operator)
+}
+
+fn multiplicative_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    left_assoc(i, unary_expression, multiplicative_operator)
+}
+
+fn unary_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    alt((
+        // negative numbers are literals
+        postfix_expression,
+        map(
+            pair(terminated(minus, multispace0), unary_expression),
+            |(_, expr)| Expr::Unary { op: UnaryOperator::Minus, expr: Box::new(expr) }
+        ),
+    ))(i)
+}
+
+enum Postfix {
+    Subscript(Expr),
+    TupleElement(u64),
+    Member(String),
+}
+
+// arr[1], tup.1, tup.name, map['key'][2]
+fn postfix_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    let (i, expr) = primary_expression(i)?;
+    fold_many0(
+        alt((
+            map(
+                delimited(pair(tag("["), multispace0), expression, pair(multispace0, tag("]"))),
+                Postfix::Subscript
+            ),
+            map(
+                preceded(tag("."), terminated(map_res(digit1, |d| str::from_utf8(d).unwrap().parse()), not(take_while1(is_sql_identifier)))),
+                Postfix::TupleElement
+            ),
+            map(
+                preceded(tag("."), sql_identifier),
+                |name| Postfix::Member(str::from_utf8(name).unwrap().to_string())
+            ),
+        )),
+        expr,
+        |expr, postfix| match postfix {
+            Postfix::Subscript(index) => Expr::Subscript { expr: Box::new(expr), index: Box::new(index) },
+            Postfix::TupleElement(index) => Expr::TupleElement { expr: Box::new(expr), index },
+            Postfix::Member(name) => Expr::Member { expr: Box::new(expr), name },
+        }
+    )(i)
+}
+
+// `tup.1` is a tuple element, not column `1` of table `tup`
+fn column(i: &[u8]) -> IResult<&[u8], Column> {
+    alt((
+        verify(column_identifier_no_alias, |c: &Column| c.table.is_none() || !c.name.bytes().all(|b| b.is_ascii_digit())),
+        map(sql_identifier, |name| str::from_utf8(name).unwrap().into()),
+    ))(i)
+}
+
+fn primary_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    alt((
+        map(literal, Expr::Literal),
+        case_expression,
+        interval_expression,
+        cast_expression,
+        extract_expression,
+        substring_expression,
+        map(function_call, Expr::Function),
+        map(
+            delimited(pair(tag("("), multispace0), nested_selection, pair(multispace0, tag(")"))),
+            |query| Expr::Subquery(Box::new(query))
+        ),
+        parenthesized_expression,
+        map(
+            delimited(pair(tag("["), multispace0), separated_list(ws_sep_comma, expression), pair(multispace0, tag("]"))),
+            Expr::Array
+        ),
+        map(column, Expr::Column),
+    ))(i)
+}
+
+fn integer_literal(i: &[u8]) -> IResult<&[u8], Literal> {
+    map_res(
+        verify(
+            pair(
+                opt(tag("-")),
+                alt((
+                    map(preceded(tag_no_case("0x"), hex_digit1), |d| (d, 16)),
+                    map(preceded(tag_no_case("0b"), take_while1(|c| c == b'0' || c == b'1')), |d| (d, 2)),
+                    map(preceded(tag_no_case("0o"), oct_digit1), |d| (d, 8)),
+                    map(digit1, |d| (d, 10)),
+                )),
+            ),
+            // `-0` stays a negation, Integer(0) would print as `0`
+            |(sign, (digits, _)): &(Option<&[u8]>, (&[u8], u32))| sign.is_none() || digits.iter().any(|d| *d != b'0'),
+        ),
+        |(sign, (digits, radix))| {
+            let digits = str::from_utf8(digits).unwrap();
+            match sign {
+                Some(_) => i64::from_str_radix(&format!("-{}", digits), radix).map(Literal::Integer),
+                None => u64::from_str_radix(digits, radix).map(Literal::UnsignedInteger),
+            }
+        }
+    )(i)
+}
+
+// 1.5, .5, 1e10, 1.5E-3, inf, -nan
+fn float_literal(i: &[u8]) -> IResult<&[u8], Literal> {
+    let exponent = |i| recognize(tuple((tag_no_case("e"), opt(alt((tag("+"), tag("-")))), digit1)))(i);
+    map(
+        recognize(pair(
+            opt(tag("-")),
+            alt((
+                recognize(tuple((digit1, tag("."), digit1, opt(exponent)))),
+                recognize(tuple((tag("."), digit1, opt(exponent)))),
+                recognize(pair(digit1, exponent)),
+                keyword("inf"),
+                keyword("nan"),
+            )),
+        )),
+        |n| Literal::Float(str::from_utf8(n).unwrap().to_lowercase())
+    )(i)
+}
+
+pub fn literal(i: &[u8]) -> IResult<&[u8], Literal> {
+    alt((
+        terminated(alt((float_literal, integer_literal)), not(take_while1(is_sql_identifier))),
+        map(raw_string_single_quoted, |s| Literal::String(String::from_utf8(s).unwrap())),
+        map(keyword("null"), |_| Literal::Null),
+        map(keyword("true"), |_| Literal::Boolean(true)),
+        map(keyword("false"), |_| Literal::Boolean(false)),
+        map(
+            delimited(pair(tag("["), multispace0), separated_list(ws_sep_comma, literal), pair(multispace0, tag("]"))),
+            Literal::Array
+        ),
+        map(
+            delimited(
+                pair(tag("{"), multispace0),
+                separated_list(
+                    ws_sep_comma,
+                    pair(literal, preceded(delimited(multispace0, tag(":"), multispace0), literal)),
+                ),
+                pair(multispace0, tag("}")),
+            ),
+            Literal::Map
+        ),
+    ))(i)
+}
+
+fn argument_list(i: &[u8]) -> IResult<&[u8], Vec<Expr>> {
+    delimited(
+        pair(tag("("), multispace0),
+        separated_list(ws_sep_comma, alt((map(tag("*"), |_| Expr::Asterisk), expression))),
+        pair(multispace0, tag(")")),
+    )(i)
+}
+
+// Arguments which may start with DISTINCT, e.g. of count(DISTINCT a)
+fn distinct_argument_list(i: &[u8]) -> IResult<&[u8], (bool, Vec<Expr>)> {
+    delimited(
+        pair(tag("("), multispace0),
+        pair(
+            map(opt(terminated(keyword("distinct"), multispace0)), |d| d.is_some()),
+            separated_list(ws_sep_comma, alt((map(tag("*"), |_| Expr::Asterisk), expression))),
+        ),
+        pair(multispace0, tag(")")),
+    )(i)
+}
+
+// name(args) or name(params)(args)
+pub fn function_call(i: &[u8]) -> IResult<&[u8], Function> {
+    map(
+        tuple((
+            take_while1(is_sql_identifier),
+            multispace0,
+            alt((
+                map(pair(argument_list, distinct_argument_list), |(params, args)| (Some(params), args)),
+                map(distinct_argument_list, |args| (None, args)),
+            )),
+        )),
+        |(name, _, (params, (distinct, args)))| {
+            let name = str::from_utf8(name).unwrap().to_string();
+            Function { name, params, distinct, args }
+        }
+    )(i)
+}
+
+fn cast_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        tuple((
+            tag_no_case("cast"),
+            multispace0,
+            tag("("),
+            multispace0,
+            expression,
+            multispace1,
+            tag_no_case("as"),
+            multispace1,
+            type_identifier,
+            multispace0,
+            tag(")"),
+        )),
+        |(_, _, _, _, expr, _, _, _, sql_type, _, _)| Expr::Cast { expr: Box::new(expr), sql_type }
+    )(i)
+}
+
+fn case_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    let when_then = |i| map(
+        tuple((
+            keyword("when"),
+            multispace1,
+            expression,
+            multispace1,
+            keyword("then"),
+            multispace1,
+            expression,
+        )),
+        |(_, _, condition, _, _, _, result)| (condition, result)
+    )(i);
+    map(
+        tuple((
+            keyword("case"),
+            multispace1,
+            opt(terminated(preceded(not(keyword("when")), expression), multispace1)),
+            separated_nonempty_list(multispace1, when_then),
+            opt(preceded(tuple((multispace1, keyword("else"), multispace1)), expression)),
+            multispace1,
+            keyword("end"),
+        )),
+        |(_, _, operand, conditions, else_result, _, _)| Expr::Case {
+            operand: operand.map(Box::new),
+            conditions,
+            else_result: else_result.map(Box::new),
+        }
+    )(i)
+}
+
+pub fn interval_unit(i: &[u8]) -> IResult<&[u8], IntervalUnit> {
+    alt((
+        map(keyword("nanosecond"), |_| IntervalUnit::Nanosecond),
+        map(keyword("microsecond"), |_| IntervalUnit::Microsecond),
+        map(keyword("millisecond"), |_| IntervalUnit::Millisecond),
+        map(keyword("second"), |_| IntervalUnit::Second),
+        map(keyword("minute"), |_| IntervalUnit::Minute),
+        map(keyword("hour"), |_| IntervalUnit::Hour),
+        map(keyword("day"), |_| IntervalUnit::Day),
+        map(keyword("week"), |_| IntervalUnit::Week),
+        map(keyword("month"), |_| IntervalUnit::Month),
+        map(keyword("quarter"), |_| IntervalUnit::Quarter),
+        map(keyword("year"), |_| IntervalUnit::Year),
+    ))(i)
+}
+
+// INTERVAL 1 DAY, INTERVAL '2' SECOND, INTERVAL -n HOUR
+pub fn interval_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        tuple((keyword("interval"), multispace1, unary_expression, multispace1, interval_unit)),
+        |(_, _, value, _, unit)| Expr::Interval { value: Box::new(value), unit }
+    )(i)
+}
+
+fn extract_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        tuple((
+            keyword("extract"),
+            multispace0,
+            tag("("),
+            multispace0,
+            interval_unit,
+            multispace1,
+            keyword("from"),
+            multispace1,
+            expression,
+            multispace0,
+            tag(")"),
+        )),
+        |(_, _, _, _, unit, _, _, _, expr, _, _)| Expr::Extract { unit, expr: Box::new(expr) }
+    )(i)
+}
+
+fn substring_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        tuple((
+            keyword("substring"),
+            multispace0,
+            tag("("),
+            multispace0,
+            expression,
+            multispace1,
+            keyword("from"),
+            multispace1,
+            expression,
+            opt(preceded(tuple((multispace1, keyword("for"), multispace1)), expression)),
+            multispace0,
+            tag(")"),
+        )),
+        |(_, _, _, _, expr, _, _, _, from, length, _, _)| Expr::Substring {
+            expr: Box::new(expr),
+            from: Box::new(from),
+            length: length.map(Box::new),
+        }
+    )(i)
+}
+
+// (expr) or a tuple (a, b, ...)
+fn parenthesized_expression(i: &[u8]) -> IResult<&[u8], Expr> {
+    map(
+        delimited(
+            pair(tag("("), multispace0),
+            separated_list(ws_sep_comma, expression),
+            pair(multispace0, tag(")")),
+        ),
+        |mut items| if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Expr::Tuple(items)
+        }
+    )(i)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn t_expression() {
+        let patterns = vec![
+            ( "rand()", "rand()" ),
+            ( "toDate(requestedAt)", "toDate(requestedAt)" ),
+            ( "(col1, coln2, rand())", "(col1, coln2, rand())" ),
+            ( "func('col')", "func('col')" ),
+            ( "func('col','df')", "func('col', 'df')" ),
+            ( "cast('val' as Date)", "CAST('val' AS Date)" ),
+            ( "CAST(x AS LowCardinality(String))", "CAST(x AS LowCardinality(String))" ),
+            ( "CAST(x AS Array(Nullable(Int64)))", "CAST(x AS Array(Nullable(Int64)))" ),
+            (
+                r#"CAST('captcha', 'Enum8(\'captcha\' = 1, \'ban\' = 2)')"#,
+                r#"CAST('captcha', 'Enum8(\'captcha\' = 1, \'ban\' = 2)')"#
+            ),
+            ( "z>1", "z > 1" ),
+            ( "t.a == 1.5", "t.a = 1.5" ),
+            ( "a <> b", "a != b" ),
+            ( "count(*)", "count(*)" ),
+            ( "count(distinct a)", "count(DISTINCT a)" ),
+            ( "uniqExact(DISTINCT a, b)", "uniqExact(DISTINCT a, b)" ),
+            ( "quantile(0.9)(DISTINCT x)", "quantile(0.9)(DISTINCT x)" ),
+            ( "quantiles(0.5, 0.9)(x)", "quantiles(0.5, 0.9)(x)" ),
+            ( "a + b * c", "a + b * c" ),
+            ( "(a + b) * c", "(a + b) * c" ),
+            ( "a - (b - c)", "a - (b - c)" ),
+            ( "(a - b) - c", "a - b - c" ),
+            ( "-x % 3", "-x % 3" ),
+            ( "-(a + 1)", "-(a + 1)" ),
+            ( "- -a", "-(-a)" ),
+            ( "-(1)", "-(1)" ),
+            ( "- - 1", "-(-(1))" ),
+            ( "-0", "-(0)" ),
+            ( "a - -1", "a - -1" ),
+            ( "(a)", "a" ),
+            ( "tuple()", "tuple()" ),
+            ( "a >= b + 1", "a >= b + 1" ),
+            ( "x = 1 AND y != 2", "x = 1 AND y != 2" ),
+            ( "a or b and c", "a OR b AND c" ),
+            ( "(a OR b) AND c", "(a OR b) AND c" ),
+            ( "a AND (b AND c)", "a AND (b AND c)" ),
+            ( "NOT x", "NOT x" ),
+            ( "not (a and b)", "NOT (a AND b)" ),
+            ( "NOT a = 1", "NOT a = 1" ),
+            ( "(NOT a) = 1", "(NOT a) = 1" ),
+            ( "not(a)", "NOT a" ),
+            ( "a || b || 'c'", "a || b || 'c'" ),
+            ( "a || (b + 1)", "a || b + 1" ),
+            ( "(a || b) + 1", "(a || b) + 1" ),
+            ( "x BETWEEN 1 AND 2 AND y", "x BETWEEN 1 AND 2 AND y" ),
+            ( "x not between 1 + 1 and (2 OR 3)", "x NOT BETWEEN 1 + 1 AND (2 OR 3)" ),
+            ( "x IS NULL", "x IS NULL" ),
+            ( "x is not null OR y", "x IS NOT NULL OR y" ),
+            ( "(x IS NULL) = 1", "(x IS NULL) = 1" ),
+            ( "s LIKE 'a%' AND s not ilike '%b'", "s LIKE 'a%' AND s NOT ILIKE '%b'" ),
+            ( "x IN (1,2)", "x IN (1, 2)" ),
+            ( "x not in (1, 2) or y in t", "x NOT IN (1, 2) OR y IN t" ),
+            ( "x global not in (select id from t)", "x GLOBAL NOT IN (SELECT id FROM t)" ),
+            ( "(a, b) IN ((1, 2))", "(a, b) IN ((1, 2))" ),
+            ( "(a, b) in (((1, 2)))", "(a, b) IN ((1, 2))" ),
+            ( "(a, b) IN ((1, 2), (3, 4))", "(a, b) IN ((1, 2), (3, 4))" ),
+            ( "x = ((1, 2))", "x = (1, 2)" ),
+            ( "c ? a : b", "c ? a : b" ),
+            ( "c1 ? a : c2 ? b : c", "c1 ? a : c2 ? b : c" ),
+            ( "(c1 ? a : c2) ? b : c", "(c1 ? a : c2) ? b : c" ),
+            ( "a = 1 OR b ? x + 1 : y", "a = 1 OR b ? x + 1 : y" ),
+            ( "if(a AND b, 1, 2)", "if(a AND b, 1, 2)" ),
+            ( "x = -1", "x = -1" ),
+            ( "-(-1)", "-(-1)" ),
+            ( "- 1", "-(1)" ),
+            ( "1.5e3 * 0x10", "1.5e3 * 16" ),
+            ( "[1, 2]", "[1, 2]" ),
+            ( "[a, b + 1]", "[a, b + 1]" ),
+            ( "has([1, 2], x) AND y IS NOT NULL", "has([1, 2], x) AND y IS NOT NULL" ),
+            ( "{'a' : 1}", "{'a': 1}" ),
+            ( "x = true OR y = NULL", "x = true OR y = NULL" ),
+            ( "arrayMap(x -> lower(x), tags)", "arrayMap(x -> lower(x), tags)" ),
+            ( "arrayFilter((k, v)->v > 0, keys, vals)", "arrayFilter((k, v) -> v > 0, keys, vals)" ),
+            ( "arrayMap((x) -> x ? 1 : 0, a)", "arrayMap(x -> x ? 1 : 0, a)" ),
+            ( "arraySort(x -> -x, arr)", "arraySort(x -> -x, arr)" ),
+            ( "arrayMap(x -> arrayMap(y -> x + y, b), a)", "arrayMap(x -> arrayMap(y -> x + y, b), a)" ),
+            ( "a->1", "a -> 1" ),
+            ( "CASE WHEN a = 1 THEN 'one' WHEN a = 2 THEN 'two' ELSE 'many' END",
+                "CASE WHEN a = 1 THEN 'one' WHEN a = 2 THEN 'two' ELSE 'many' END" ),
+            ( "case a when 1 then b+1\n  end", "CASE a WHEN 1 THEN b + 1 END" ),
+            ( "CASE WHEN x THEN y END + 1", "CASE WHEN x THEN y END + 1" ),
+            ( "multiIf(a > 1, 'a', b > 1, 'b', 'c')", "multiIf(a > 1, 'a', b > 1, 'b', 'c')" ),
+            ( "d + INTERVAL 1 DAY", "d + INTERVAL 1 DAY" ),
+            ( "now() - interval 15 minute", "now() - INTERVAL 15 MINUTE" ),
+            ( "INTERVAL '2' second", "INTERVAL '2' SECOND" ),
+            ( "INTERVAL -n HOUR", "INTERVAL -n HOUR" ),
+            ( "interval", "interval" ),
+            ( "EXTRACT(DAY FROM d)", "EXTRACT(DAY FROM d)" ),
+            ( "extract(year from toDate(ts))", "EXTRACT(YEAR FROM toDate(ts))" ),
+            ( "extract(s, '[0-9]+')", "extract(s, '[0-9]+')" ),
+            ( "SUBSTRING(s FROM 1 FOR 2)", "SUBSTRING(s FROM 1 FOR 2)" ),
+            ( "substring(s from 3)", "SUBSTRING(s FROM 3)" ),
+            ( "substring(s, 1, 2)", "substring(s, 1, 2)" ),
+            ( "arr[1]", "arr[1]" ),
+            ( "arr[ length(arr) ]", "arr[length(arr)]" ),
+            ( "map['key']", "map['key']" ),
+            ( "m['a']['b'] + 1", "m['a']['b'] + 1" ),
+            ( "[1, 2, 3][2]", "[1, 2, 3][2]" ),
+            ( "tup.1", "tup.1" ),
+            ( "t.tup.2", "t.tup.2" ),
+            ( "(1, 'a').2", "(1, 'a').2" ),
+            ( "nested.field", "nested.field" ),
+            ( "t.nested.field", "t.nested.field" ),
+            ( "f(x).name[1].2", "f(x).name[1].2" ),
+            ( "(a + b)[1]", "(a + b)[1]" ),
+            ( "-arr[1]", "-arr[1]" ),
+            ( "(-arr)[1]", "(-arr)[1]" ),
+            ( "arrayMap(x -> x.1, pairs)", "arrayMap(x -> x.1, pairs)" ),
+            (
+                "assumeNotNull(if(length(deviceId) > 1, murmurHash3_64(deviceId), rand()))",
+                "assumeNotNull(if(length(deviceId) > 1, murmurHash3_64(deviceId), rand()))"
+            ),
+        ];
+        parse_set_for_test(|i| expression(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns.into_iter().map(|(s, d)| (s, d.to_string())).collect());
+    }
+
+    #[test]
+    fn t_literal() {
+        let patterns = vec![
+            ( "0", Literal::UnsignedInteger(0) ),
+            ( "18446744073709551615", Literal::UnsignedInteger(u64::MAX) ),
+            ( "-42", Literal::Integer(-42) ),
+            ( "0x1F", Literal::UnsignedInteger(31) ),
+            ( "0b101", Literal::UnsignedInteger(5) ),
+            ( "0o17", Literal::UnsignedInteger(15) ),
+            ( "-0xff", Literal::Integer(-255) ),
+            ( "1.5", Literal::Float("1.5".into()) ),
+            ( "-.5", Literal::Float("-.5".into()) ),
+            ( "1e10", Literal::Float("1e10".into()) ),
+            ( "2.5E-3", Literal::Float("2.5e-3".into()) ),
+            ( "inf", Literal::Float("inf".into()) ),
+            ( "-Inf", Literal::Float("-inf".into()) ),
+            ( "NaN", Literal::Float("nan".into()) ),
+            ( "NULL", Literal::Null ),
+            ( "true", Literal::Boolean(true) ),
+            ( "FALSE", Literal::Boolean(false) ),
+            ( "'it''s'", Literal::String("it's".into()) ),
+            (
+                "[1, -2, 3]",
+                Literal::Array(vec![Literal::UnsignedInteger(1), Literal::Integer(-2), Literal::UnsignedInteger(3)])
+            ),
+            ( "[[], ['a']]", Literal::Array(vec![Literal::Array(vec![]), Literal::Array(vec![Literal::String("a".into())])]) ),
+            (
+                "{'a': 1, 'b':NULL}",
+                Literal::Map(vec![
+                    (Literal::String("a".into()), Literal::UnsignedInteger(1)),
+                    (Literal::String("b".into()), Literal::Null),
+                ])
+            ),
+        ];
+        parse_set_for_test(literal, patterns);
+
+        assert!(literal(b"18446744073709551616").is_err());
+        assert!(literal(b"0b102").is_err());
+        assert!(literal(b"information").is_err());
+    }
+
+    #[test]
+    fn t_expression_precedence() {
+        let parse = |e: &str| expression(e.as_bytes()).unwrap().1;
+        let binary = |op, left: Expr, right: Expr| Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+        let one = || Expr::Literal(Literal::UnsignedInteger(1));
+
+        assert_eq!(
+            parse("a OR b AND NOT c = 1"),
+            binary(
+                BinaryOperator::Or,
+                "a".into(),
+                binary(
+                    BinaryOperator::And,
+                    "b".into(),
+                    Expr::Unary {
+                        op: UnaryOperator::Not,
+                        expr: Box::new(binary(BinaryOperator::Equal, "c".into(), one())),
+                    },
+                ),
+            )
+        );
+        assert_eq!(
+            parse("a - b - c"),
+            binary(BinaryOperator::Minus, binary(BinaryOperator::Minus, "a".into(), "b".into()), "c".into())
+        );
+        assert_eq!(
+            parse("c ? a : b ? 1 : d"),
+            Expr::Ternary {
+                condition: Box::new("c".into()),
+                then: Box::new("a".into()),
+                otherwise: Box::new(Expr::Ternary {
+                    condition: Box::new("b".into()),
+                    then: Box::new(one()),
+                    otherwise: Box::new("d".into()),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn t_expression_round_trip() {
+        let sources = vec![
+            "(a, b) IN ((1, 2))",
+            "(a, b) NOT IN ((1, 2), (3, 4))",
+            "x GLOBAL IN ((1, 'a'))",
+            "x IN (1, 2)",
+            "-(1)",
+            "- - 1",
+            "- -1",
+            "-0",
+            "-0.0",
+            "-(inf)",
+            "-(1.5)",
+            "-(1)[1]",
+            "-(-x)",
+            "a - -1",
+            "-9223372036854775808",
+            "c ? (x -> x) : y",
+            "c ? y : (x -> x)",
+            "c ? (a ? b : d) : e",
+        ];
+        for source in sources {
+            let (_, expr) = expression(source.as_bytes()).unwrap();
+            let display = expr.to_string();
+            let (rest, reparsed) = expression(display.as_bytes()).unwrap();
+            assert!(rest.is_empty(), "{} => {}", source, expr);
+            assert_eq!(expr, reparsed, "{} => {}", source, expr);
+        }
+    }
+
+    #[test]
+    fn t_expression_ast() {
+        let (rest, expr) = expression(b"a + 2 * f(b) -- comment").unwrap();
+        assert_eq!(rest, b" -- comment");
+        assert_eq!(expr, Expr::Binary {
+            op: BinaryOperator::Plus,
+            left: Box::new("a".into()),
+            right: Box::new(Expr::Binary {
+                op: BinaryOperator::Multiply,
+                left: Box::new(Expr::Literal(Literal::UnsignedInteger(2))),
+                right: Box::new(Expr::Function(Function {
+                    name: "f".into(),
+                    params: None,
+                    distinct: false,
+                    args: vec!["b".into()],
+                })),
+            }),
+        });
+
+        assert_eq!(expression(b"tup.1").unwrap().1, Expr::TupleElement {
+            expr: Box::new("tup".into()),
+            index: 1,
+        });
+        assert_eq!(expression(b"m['key']").unwrap().1, Expr::Subscript {
+            expr: Box::new("m".into()),
+            index: Box::new(Expr::Literal(Literal::String("key".into()))),
+        });
+    }
+}
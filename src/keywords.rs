@@ -5,7 +5,7 @@ use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
 use nom::combinator::peek;
 use nom::sequence::terminated;
-use nom::IResult;
+use crate::IResult;
 
 
 fn keyword_follow_char(i: &[u8]) -> IResult<&[u8], &[u8]> {
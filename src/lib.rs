@@ -2,20 +2,18 @@
 //extern crate nom;
 
 use std::str;
-use std::str::FromStr;
 use std::fmt; 
 
 use nom::{
-    IResult,
     InputLength,
-    error::{ ParseError},
+    error::{ context, ParseError as NomParseError},
     branch::alt,
     sequence::{delimited, preceded, terminated, tuple, pair},
-    combinator::{map, opt, not, peek, recognize},
-    character::complete::{digit1, multispace0, multispace1, line_ending, one_of},
+    combinator::{cut, map, map_res, opt, not, peek, recognize, verify},
+    character::complete::digit1,
     character::is_alphanumeric,
     bytes::complete::{is_not, tag, tag_no_case, take, take_until, take_while1},
-    multi::{fold_many0, many1, separated_list,},
+    multi::{fold_many0, many0, many1, separated_list, separated_nonempty_list},
 };
 pub use nom::{
     self,
@@ -24,19 +22,38 @@ pub use nom::{
 };
 
 mod keywords;
+pub mod error;
 pub mod table;
 pub mod column;
 pub mod create;
+pub mod expression;
+pub mod select;
 
+pub use error::{ParseError, SyntaxError};
 use keywords::sql_keyword;
 use table::Table;
 use column::Column;
 use create::{
     CreateTableStatement,
+    CreateMaterializedViewStatement,
+    CreateViewStatement,
+    CreateLiveViewStatement,
+    CreateWindowViewStatement,
+    CreateDatabaseStatement,
     creation,
+    database_creation,
+    materialized_view_creation,
+    view_creation,
+    live_view_creation,
+    window_view_creation,
 };
+use expression::{IntervalUnit, Literal, interval_unit, literal};
+use select::{SelectQuery, selection};
 
-fn eof<I: Copy + InputLength, E: ParseError<I>>(input: I) -> IResult<I, I, E> {
+/// Result of the parsers in this crate, failing with `SyntaxError` by default
+pub type IResult<I, O, E = SyntaxError<I>> = nom::IResult<I, O, E>;
+
+fn eof<I: Copy + InputLength, E: NomParseError<I>>(input: I) -> IResult<I, I, E> {
     if input.input_len() == 0 {
         Ok((input, input))
     } else {
@@ -51,11 +68,42 @@ pub fn ws_sep_comma(i: &[u8]) -> IResult<&[u8], &[u8]> {
 
 pub fn statement_terminator(i: &[u8]) -> IResult<&[u8], ()> {
     let (remaining_input, _) =
-        delimited(multispace0, alt((tag(";"), line_ending, eof)), multispace0)(i)?;
+        delimited(multispace0, alt((tag(";"), eof)), multispace0)(i)?;
 
     Ok((remaining_input, ()))
 }
 
+// `-- comment`, `# comment` (up to the end of line) or `/* comment */`
+pub fn sql_comment(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    alt((
+        recognize(pair(alt((tag("--"), tag("#"))), opt(is_not("\n")))),
+        recognize(tuple((tag("/*"), take_until("*/"), tag("*/")))),
+    ))(i)
+}
+
+/// Optional whitespace between tokens, comments count as whitespace.
+///
+/// Replaces nom's `multispace0` in all parsers of this crate.
+pub fn multispace0(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    recognize(many0(alt((nom::character::complete::multispace1, sql_comment))))(i)
+}
+
+/// Mandatory whitespace between tokens, comments count as whitespace.
+pub fn multispace1(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    recognize(many1(alt((nom::character::complete::multispace1, sql_comment))))(i)
+}
+
+pub fn schema_table_reference_no_alias(i: &[u8]) -> IResult<&[u8], Table> {
+    map(
+        pair(opt(terminated(sql_identifier, tag("."))), sql_identifier),
+        |(schema, name)| Table {
+            name: String::from(str::from_utf8(name).unwrap()),
+            alias: None,
+            schema: schema.map(|s| String::from(str::from_utf8(s).unwrap())),
+        }
+    )(i)
+}
+
 pub fn schema_table_reference(i: &[u8]) -> IResult<&[u8], Table> {
     map(
 		tuple((
@@ -65,14 +113,8 @@ pub fn schema_table_reference(i: &[u8]) -> IResult<&[u8], Table> {
 		)),
 	|tup| Table {
         name: String::from(str::from_utf8(tup.1).unwrap()),
-        alias: match tup.2 {
-            Some(a) => Some(String::from(a)),
-            None => None,
-        },
-        schema: match tup.0 {
-            Some((schema, _)) => Some(String::from(str::from_utf8(schema).unwrap())),
-            None => None,
-        },
+        alias: tup.2.map(String::from),
+        schema: tup.0.map(|(schema, _)| String::from(str::from_utf8(schema).unwrap())),
     })(i)
 }
 
@@ -96,20 +138,17 @@ pub fn column_identifier_no_alias(i: &[u8]) -> IResult<&[u8], Column> {
     map(table_parser, |tup| Column {
         name: str::from_utf8(tup.1).unwrap().to_string(),
         alias: None,
-        table: match tup.0 {
-            None => None,
-            Some(t) => Some(str::from_utf8(t).unwrap().to_string()),
-        },
+        table: tup.0.map(|t| str::from_utf8(t).unwrap().to_string()),
     })(i)
 }
 
 pub fn is_sql_identifier(chr: u8) -> bool {
-    is_alphanumeric(chr) || chr == '_' as u8 || chr == '@' as u8
+    is_alphanumeric(chr) || chr == b'_' || chr == b'@'
 }
 
 pub fn sql_identifier(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    let is_not_doublequote = |chr| chr != '"' as u8;
-    let is_not_backquote = |chr| chr != '`' as u8;
+    let is_not_doublequote = |chr| chr != b'"';
+    let is_not_backquote = |chr| chr != b'`';
     alt((
         correct_identifier,
         delimited(tag("`"), take_while1(is_not_backquote), tag("`")),
@@ -121,29 +160,59 @@ pub fn correct_identifier(i: &[u8]) -> IResult<&[u8], &[u8]> {
     preceded(not(peek(sql_keyword)), take_while1(is_sql_identifier))(i)
 }
 
+// Single-quoted string literal
+pub fn escape_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
 pub fn escape_identifier(identifier: &str) -> String {
-    if correct_identifier(identifier.as_bytes()).is_ok() {
-        identifier.to_owned()
-    } else {
-        format!("`{}`", identifier)
+    match correct_identifier(identifier.as_bytes()) {
+        Ok((&[], _)) => identifier.to_owned(),
+        _ => format!("`{}`", identifier),
     }
-
 }
 
 
 
+// statements are parsed one at a time, boxing them buys nothing
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum SqlQuery {
     CreateTable(CreateTableStatement),
+    CreateMaterializedView(CreateMaterializedViewStatement),
+    CreateView(CreateViewStatement),
+    CreateLiveView(CreateLiveViewStatement),
+    CreateWindowView(CreateWindowViewStatement),
+    CreateDatabase(CreateDatabaseStatement),
+    Select(SelectQuery),
 }
 impl fmt::Display for SqlQuery {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             SqlQuery::CreateTable(ref s) => write!(f, "{}", s),
+            SqlQuery::CreateMaterializedView(ref s) => write!(f, "{}", s),
+            SqlQuery::CreateView(ref s) => write!(f, "{}", s),
+            SqlQuery::CreateLiveView(ref s) => write!(f, "{}", s),
+            SqlQuery::CreateWindowView(ref s) => write!(f, "{}", s),
+            SqlQuery::CreateDatabase(ref s) => write!(f, "{}", s),
+            SqlQuery::Select(ref s) => write!(f, "{};", s),
         }
     }
 }
 
+fn aggregate_function_arguments(function: &str, params: &Option<Vec<Literal>>, args: &[SqlType]) -> String {
+    let mut items = vec![match params {
+        Some(params) => format!(
+            "{}({})",
+            function,
+            params.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(", ")
+        ),
+        None => function.to_string(),
+    }];
+    items.extend(args.iter().map(|t| t.to_string()));
+    items.join(", ")
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum TypeSize16 {
     B8,
@@ -164,6 +233,8 @@ pub enum TypeSize {
     B16,
     B32,
     B64,
+    B128,
+    B256,
 }
 impl fmt::Display for TypeSize {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -172,6 +243,8 @@ impl fmt::Display for TypeSize {
             TypeSize::B16 => write!(f, "16"),
             TypeSize::B32 => write!(f, "32"),
             TypeSize::B64 => write!(f, "64"),
+            TypeSize::B128 => write!(f, "128"),
+            TypeSize::B256 => write!(f, "256"),
         }
     }
 }
@@ -184,12 +257,75 @@ pub enum SqlType {
     UnsignedInt(TypeSize),
     Enum(Option<TypeSize16>, Vec<(String, i16)>),
     Date,
+    Date32,
     DateTime(Option<String>),
+    // precision of the sub-second ticks and time zone
+    DateTime64(u8, Option<String>),
     Float32,
     Float64,
     FixedString(usize),
     IPv4,
     IPv6,
+    UUID,
+    Bool,
+    // Decimal32(S), Decimal64(S) etc. are stored with their precision,
+    // e.g. Decimal64(4) is Decimal(18, 4)
+    Decimal { precision: u8, scale: u8 },
+    Array(Box<SqlType>),
+    // elements of a named tuple have names, Tuple(a String, b UInt8)
+    Tuple(Vec<(Option<String>, SqlType)>),
+    Map(Box<SqlType>, Box<SqlType>),
+    Nested(Vec<(String, SqlType)>),
+    Nullable(Box<SqlType>),
+    LowCardinality(Box<SqlType>),
+    Point,
+    Ring,
+    LineString,
+    MultiLineString,
+    Polygon,
+    MultiPolygon,
+    // Object('json')
+    Object(String),
+    JSON,
+    // type of empty values, e.g. Array(Nothing) of `[]`
+    Nothing,
+    // IntervalDay, IntervalSecond, ...
+    Interval(IntervalUnit),
+    // AggregateFunction(quantiles(0.5, 0.9), Float64)
+    AggregateFunction {
+        function: String,
+        params: Option<Vec<Literal>>,
+        args: Vec<SqlType>,
+    },
+    // SimpleAggregateFunction(max, DateTime)
+    SimpleAggregateFunction {
+        function: String,
+        params: Option<Vec<Literal>>,
+        args: Vec<SqlType>,
+    },
+}
+
+impl SqlType {
+    // Only plain scalar types can be Nullable, new types are rejected until listed here
+    fn can_be_nullable(&self) -> bool {
+        matches!(self,
+            SqlType::String | SqlType::FixedString(_)
+            | SqlType::Int(_) | SqlType::UnsignedInt(_) | SqlType::Float32 | SqlType::Float64
+            | SqlType::Decimal { .. } | SqlType::Bool | SqlType::Enum(..)
+            | SqlType::Date | SqlType::Date32 | SqlType::DateTime(_) | SqlType::DateTime64(..)
+            | SqlType::IPv4 | SqlType::IPv6 | SqlType::UUID
+            | SqlType::Interval(_) | SqlType::Nothing
+        )
+    }
+
+    // LowCardinality is a dictionary of plain values, LowCardinality(Nullable(T)) included
+    fn can_be_low_cardinality(&self) -> bool {
+        match self {
+            SqlType::Nullable(t) => t.can_be_low_cardinality(),
+            SqlType::Nothing => false,
+            t => t.can_be_nullable(),
+        }
+    }
 }
 
 impl fmt::Display for SqlType {
@@ -202,133 +338,74 @@ impl fmt::Display for SqlType {
                 size.as_ref().map(|size| format!("{}", size)).unwrap_or("".into()),
                 values
                     .iter()
-                    .map(|(name, num)| format!("'{}' = {}", name, num)) 
+                    .map(|(name, num)| format!("{} = {}", escape_string(name), num))
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
             SqlType::Date => write!(f, "Date"),
+            SqlType::Date32 => write!(f, "Date32"),
             SqlType::DateTime(None) => write!(f, "DateTime"),
-            SqlType::DateTime(Some(timezone)) => write!(f, "DateTime({})", timezone),
+            SqlType::DateTime(Some(timezone)) => write!(f, "DateTime({})", escape_string(timezone)),
+            SqlType::DateTime64(precision, None) => write!(f, "DateTime64({})", precision),
+            SqlType::DateTime64(precision, Some(timezone)) =>
+                write!(f, "DateTime64({}, {})", precision, escape_string(timezone)),
             SqlType::Float32 => write!(f, "Float32"),
             SqlType::Float64 => write!(f, "Float64"),
             SqlType::FixedString(size) => write!(f, "FixedString({})", size),
             SqlType::IPv4 => write!(f, "IPv4"),
             SqlType::IPv6 => write!(f, "IPv6"),
+            SqlType::UUID => write!(f, "UUID"),
+            SqlType::Bool => write!(f, "Bool"),
+            SqlType::Decimal { precision, scale } => write!(f, "Decimal({}, {})", precision, scale),
+            SqlType::Array(item) => write!(f, "Array({})", item),
+            SqlType::Tuple(items) => write!(f, "Tuple({})",
+                items
+                    .iter()
+                    .map(|(name, t)| match name {
+                        Some(name) => format!("{} {}", escape_identifier(name), t),
+                        None => format!("{}", t),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SqlType::Map(key, value) => write!(f, "Map({}, {})", key, value),
+            SqlType::Nested(fields) => write!(f, "Nested({})",
+                fields
+                    .iter()
+                    .map(|(name, t)| format!("{} {}", escape_identifier(name), t))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SqlType::Nullable(t) => write!(f, "Nullable({})", t),
+            SqlType::LowCardinality(t) => write!(f, "LowCardinality({})", t),
+            SqlType::Point => write!(f, "Point"),
+            SqlType::Ring => write!(f, "Ring"),
+            SqlType::LineString => write!(f, "LineString"),
+            SqlType::MultiLineString => write!(f, "MultiLineString"),
+            SqlType::Polygon => write!(f, "Polygon"),
+            SqlType::MultiPolygon => write!(f, "MultiPolygon"),
+            SqlType::Object(schema) => write!(f, "Object({})", escape_string(schema)),
+            SqlType::JSON => write!(f, "JSON"),
+            SqlType::Nothing => write!(f, "Nothing"),
+            SqlType::Interval(unit) => {
+                // DAY -> IntervalDay
+                let unit = unit.to_string();
+                write!(f, "Interval{}{}", &unit[..1], unit[1..].to_lowercase())
+            },
+            SqlType::AggregateFunction { function, params, args } =>
+                write!(f, "AggregateFunction({})", aggregate_function_arguments(function, params, args)),
+            SqlType::SimpleAggregateFunction { function, params, args } =>
+                write!(f, "SimpleAggregateFunction({})", aggregate_function_arguments(function, params, args)),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct SqlTypeOpts {
-    pub ftype: SqlType,
-    pub nullable: bool,
-    pub lowcardinality: bool,
-}
-
-impl fmt::Display for SqlTypeOpts{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match (&self.ftype, &self.lowcardinality, &self.nullable) {
-            (t, false, false) => write!(f,"{}", t),
-            (t, false, true) => write!(f,"Nullable({})", t),
-            (t, true, false) => write!(f,"LowCardinality({})", t),
-            (t, true, true) => write!(f,"LowCardinality(Nullable({}))", t),
-        }
-    }
-}
-
-
-fn ttl_expression(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    //date + INTERVAL 1 DAY
-    let ttl = map(
-        sql_identifier,
-        |name| name,
-    );
-    let ttl_interval = map(
-        recognize(tuple((
-            multispace0,
-            sql_identifier,
-            multispace0,
-            tag_no_case("INTERVAL"),
-            multispace1,
-            alt(( tag("+"), tag("-") )),
-            multispace1,
-            digit1,
-            multispace0,
-            alt((
-                tag_no_case("SECOND"),
-                tag_no_case("MINUTE"),
-                tag_no_case("HOUR"),
-                tag_no_case("DAY"),
-                tag_no_case("WEEK"),
-                tag_no_case("MONTH"),
-                tag_no_case("QUARTER"),
-                tag_no_case("YEAR"),
-            ))
-        ))),
-        |interval| interval,
-    );
-
-    alt((
-            ttl_interval,
-            ttl,
-    ))(i)
-}
-
-fn sql_expression(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    alt((
-        recognize(tuple((
-            sql_simple_expression,
-            multispace0,
-            one_of("+-*/<>"),
-            multispace0,
-            sql_simple_expression,
-        ))),
-        sql_simple_expression,
-    ))(i)
-}
-fn sql_simple_expression(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    alt((
-        sql_function,
-        sql_cast_function,
-        sql_tuple,
-        recognize(raw_string_single_quoted),
-        recognize(raw_string_double_quoted),
-        sql_identifier,
-    ))(i)
-}
-fn sql_function(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        sql_identifier,
-        multispace0,
-        sql_tuple,
-    )))(i)
-}
-
-fn sql_tuple(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        tag("("),
-        separated_list(ws_sep_comma, sql_expression),
-        tag(")"),
-    )))(i)
-}
 
-fn sql_cast_function(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    recognize(tuple((
-        tag_no_case("CAST"),
-        multispace0,
-        tag("("),
-        sql_expression,
-        multispace0,
-        alt((tag(","), tag_no_case("AS"))),
-        multispace0,
-        sql_expression,
-        multispace0,
-        tag(")"),
-    )))(i)
-}
 
-fn type_size_suffix64(i: &[u8]) -> IResult<&[u8], TypeSize> {
+fn type_size_suffix(i: &[u8]) -> IResult<&[u8], TypeSize> {
     alt((
+        map(tag_no_case("128"), |_| TypeSize::B128),
+        map(tag_no_case("256"), |_| TypeSize::B256),
         map(tag_no_case("8"), |_| TypeSize::B8),
         map(tag_no_case("16"), |_| TypeSize::B16),
         map(tag_no_case("32"), |_| TypeSize::B32),
@@ -383,110 +460,492 @@ fn raw_string_single_quoted(i: &[u8]) -> IResult<&[u8], Vec<u8>> {
     raw_string_quoted(i, true)
 }
 
-fn raw_string_double_quoted(i: &[u8]) -> IResult<&[u8], Vec<u8>> {
-    raw_string_quoted(i, false)
+
+fn decimal_number(i: &[u8]) -> IResult<&[u8], u8> {
+    map_res(digit1, |d| str::from_utf8(d).unwrap().parse::<u8>())(i)
 }
 
-// A SQL type specifier.
-fn type_identifier(i: &[u8]) -> IResult<&[u8], SqlType> {
-    let enum_value = map(
+// Decimal(P[, S]), Decimal32(S), Decimal64(S), Decimal128(S), Decimal256(S)
+fn decimal_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    let scale_arg = |max_scale: u8, expected: &'static str| {
+        delimited(
+            tuple((multispace0, tag("("), multispace0)),
+            cut(context(expected, verify(decimal_number, move |s| *s <= max_scale))),
+            pair(multispace0, tag(")")),
+        )
+    };
+    let (i, _) = tag_no_case("decimal")(i)?;
+    let (i, size) = opt(alt((tag("32"), tag("64"), tag("128"), tag("256"))))(i)?;
+    let (i, (precision, scale)) = match size {
+        Some(b"32") => map(scale_arg(9, "Decimal32 scale from 0 to 9"), |s| (9, s))(i)?,
+        Some(b"64") => map(scale_arg(18, "Decimal64 scale from 0 to 18"), |s| (18, s))(i)?,
+        Some(b"128") => map(scale_arg(38, "Decimal128 scale from 0 to 38"), |s| (38, s))(i)?,
+        Some(_) => map(scale_arg(76, "Decimal256 scale from 0 to 76"), |s| (76, s))(i)?,
+        None => {
+            let (i, precision) = preceded(
+                tuple((multispace0, tag("("), multispace0)),
+                cut(context(
+                    "Decimal precision from 1 to 76",
+                    verify(decimal_number, |p| (1..=76).contains(p)),
+                )),
+            )(i)?;
+            let (i, scale) = opt(preceded(
+                ws_sep_comma,
+                cut(context(
+                    "Decimal scale not greater than precision",
+                    verify(decimal_number, |s| *s <= precision),
+                )),
+            ))(i)?;
+            let (i, _) = pair(multispace0, tag(")"))(i)?;
+            (i, (precision, scale.unwrap_or(0)))
+        },
+    };
+    Ok((i, SqlType::Decimal { precision, scale }))
+}
+
+// `name Type` element of a named Tuple or Nested, keywords are valid names
+fn named_type(i: &[u8]) -> IResult<&[u8], (String, SqlType)> {
+    map(
         tuple((
-            multispace0,
-            map(
-                delimited(tag("'"), take_until("'"), tag("'")),
-                |s: &[u8]| {
-                    String::from_utf8(s.to_vec()).unwrap()
-                },
-            ),
-            multispace0,
-            tag("="),
-            multispace0,
-            digit1,
+            alt((take_while1(is_sql_identifier), sql_identifier)),
+            multispace1,
+            type_identifier,
         )),
-        |(_, name, _, _, _, num)| (name.to_string(), i16::from_str(str::from_utf8(num).unwrap()).unwrap())
-    );
+        |(name, _, t)| (str::from_utf8(name).unwrap().to_string(), t)
+    )(i)
+}
+
+fn type_arguments<'a, O, F>(item: F) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], Vec<O>>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    delimited(
+        tuple((multispace0, tag("("), multispace0)),
+        separated_nonempty_list(ws_sep_comma, item),
+        pair(multispace0, tag(")")),
+    )
+}
 
+// Array(T), Tuple(T1, T2), Tuple(a T1, b T2), Map(K, V), Nested(a T1, b T2)
+fn composite_type(i: &[u8]) -> IResult<&[u8], SqlType> {
     alt((
         map(
-            tuple((
-                    tag_no_case("int"),
-                    type_size_suffix64,
-            )),
-            |t| SqlType::Int(t.1)
+            preceded(
+                tag_no_case("array"),
+                delimited(
+                    tuple((multispace0, tag("("), multispace0)),
+                    type_identifier,
+                    pair(multispace0, tag(")")),
+                ),
+            ),
+            |item| SqlType::Array(Box::new(item))
         ),
         map(
-            tuple((
-                    tag_no_case("uint"),
-                    type_size_suffix64,
-            )),
-            |t| SqlType::UnsignedInt(t.1)
+            preceded(
+                tag_no_case("tuple"),
+                alt((
+                    // the empty Tuple() type of tuple()
+                    map(tuple((multispace0, tag("("), multispace0, tag(")"))), |_| vec![]),
+                    type_arguments(alt((
+                        map(named_type, |(name, t)| (Some(name), t)),
+                        map(type_identifier, |t| (None, t)),
+                    ))),
+                )),
+            ),
+            SqlType::Tuple
+        ),
+        map(
+            preceded(
+                tag_no_case("map"),
+                tuple((
+                    tuple((multispace0, tag("("), multispace0)),
+                    type_identifier,
+                    ws_sep_comma,
+                    type_identifier,
+                    pair(multispace0, tag(")")),
+                )),
+            ),
+            |(_, key, _, value, _)| SqlType::Map(Box::new(key), Box::new(value))
         ),
+        map(preceded(tag_no_case("nested"), type_arguments(named_type)), SqlType::Nested),
+    ))(i)
+}
+
+// Whole type name, `Bool` must not match the start of `Boolean`
+fn type_name<'a>(name: &'static str) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
+    terminated(tag_no_case(name), not(take_while1(is_sql_identifier)))
+}
+
+// Date, Date32, DateTime[('tz')], DateTime64(precision[, 'tz'])
+fn date_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    let timezone = |i| map(
+        raw_string_single_quoted,
+        |tz| String::from_utf8(tz).unwrap()
+    )(i);
+    alt((
         map(
             tuple((
-                    tag_no_case("enum"),
-                    opt(type_size_suffix16),
-                    tag("("),
-                    many1(terminated(enum_value, opt(ws_sep_comma))),
-                    tag(")"),
+                tag_no_case("datetime64"),
+                tuple((multispace0, tag("("), multispace0)),
+                cut(context(
+                    "DateTime64 precision from 0 to 9",
+                    verify(decimal_number, |p| *p <= 9),
+                )),
+                opt(preceded(ws_sep_comma, timezone)),
+                pair(multispace0, tag(")")),
             )),
-            |(_,size,_,values,_)| SqlType::Enum(size, values)
+            |(_, _, precision, timezone, _)| SqlType::DateTime64(precision, timezone)
         ),
-        map(tag_no_case("string"), |_| SqlType::String),
-        map(tag_no_case("float32"), |_| SqlType::Float32),
-        map(tag_no_case("float64"), |_| SqlType::Float64),
         map(
             tuple((
                 tag_no_case("datetime"),
                 multispace0,
-                opt(map(
-                    tuple((
-                        tag("("),
-                        multispace0,
-                        delimited(tag("'"), take_until("'"), tag("'")),
-                        multispace0,
-                        tag(")"),
-                    )),
-                    |(_, _, timezone, _, _)| str::from_utf8(timezone).unwrap().to_string()
+                opt(delimited(
+                    pair(tag("("), multispace0),
+                    timezone,
+                    pair(multispace0, tag(")")),
                 )),
             )),
             |(_, _, timezone)| SqlType::DateTime(timezone)
         ),
+        map(tag_no_case("date32"), |_| SqlType::Date32),
         map(tag_no_case("date"), |_| SqlType::Date),
+    ))(i)
+}
+
+// Case-insensitive SQL standard and MySQL compatible names, e.g. BIGINT,
+// VARCHAR(255), DOUBLE PRECISION, INT UNSIGNED
+fn sql_alias_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    let integer = |name: &'static str, size: TypeSize| map(
+        pair(type_name(name), opt(preceded(multispace1, type_name("unsigned")))),
+        move |(_, unsigned)| match unsigned {
+            Some(_) => SqlType::UnsignedInt(size.clone()),
+            None => SqlType::Int(size.clone()),
+        }
+    );
+    // the length of character types is ignored
+    let string = |name: &'static str| terminated(
+        type_name(name),
+        opt(tuple((multispace0, tag("("), multispace0, digit1, multispace0, tag(")")))),
+    );
+    alt((
+        alt((
+            integer("tinyint", TypeSize::B8),
+            integer("int1", TypeSize::B8),
+            integer("smallint", TypeSize::B16),
+            integer("int", TypeSize::B32),
+            integer("integer", TypeSize::B32),
+            integer("bigint", TypeSize::B64),
+        )),
+        map(
+            alt((
+                string("varchar"),
+                string("char"),
+                string("text"),
+                string("tinytext"),
+                string("mediumtext"),
+                string("longtext"),
+                string("blob"),
+                string("binary"),
+                string("varbinary"),
+            )),
+            |_| SqlType::String
+        ),
+        map(alt((type_name("float"), type_name("real"))), |_| SqlType::Float32),
+        map(
+            pair(type_name("double"), opt(preceded(multispace1, type_name("precision")))),
+            |_| SqlType::Float64
+        ),
+        map(type_name("boolean"), |_| SqlType::Bool),
+    ))(i)
+}
+
+// Geo, semi-structured and special purpose types
+fn special_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    alt((
+        map(type_name("point"), |_| SqlType::Point),
+        map(type_name("ring"), |_| SqlType::Ring),
+        map(type_name("linestring"), |_| SqlType::LineString),
+        map(type_name("multilinestring"), |_| SqlType::MultiLineString),
+        map(type_name("polygon"), |_| SqlType::Polygon),
+        map(type_name("multipolygon"), |_| SqlType::MultiPolygon),
+        map(
+            delimited(
+                tuple((tag_no_case("object"), multispace0, tag("("), multispace0)),
+                raw_string_single_quoted,
+                pair(multispace0, tag(")")),
+            ),
+            |schema| SqlType::Object(String::from_utf8(schema).unwrap())
+        ),
+        map(type_name("json"), |_| SqlType::JSON),
+        map(type_name("nothing"), |_| SqlType::Nothing),
+        map(preceded(tag_no_case("interval"), interval_unit), SqlType::Interval),
+    ))(i)
+}
+
+// AggregateFunction(name[(params)], types...), SimpleAggregateFunction(name, type)
+fn aggregate_function_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    map(
+        tuple((
+            alt((
+                map(tag_no_case("AggregateFunction"), |_| false),
+                map(tag_no_case("SimpleAggregateFunction"), |_| true),
+            )),
+            tuple((multispace0, tag("("), multispace0)),
+            context("aggregate function", take_while1(is_sql_identifier)),
+            opt(delimited(
+                tuple((multispace0, tag("("), multispace0)),
+                separated_list(ws_sep_comma, literal),
+                pair(multispace0, tag(")")),
+            )),
+            many0(preceded(ws_sep_comma, type_identifier)),
+            pair(multispace0, tag(")")),
+        )),
+        |(simple, _, function, params, args, _)| {
+            let function = str::from_utf8(function).unwrap().to_string();
+            if simple {
+                SqlType::SimpleAggregateFunction { function, params, args }
+            } else {
+                SqlType::AggregateFunction { function, params, args }
+            }
+        }
+    )(i)
+}
+
+// Enum8('a' = 1, 'b' = -1), Enum16(...), values of Enum('a', 'b') are
+// numbered from 1, a value without a number follows the previous one
+fn enum_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    let (i, (_, size, _)) = tuple((
+        tag_no_case("enum"),
+        opt(type_size_suffix16),
+        tuple((multispace0, tag("("), multispace0)),
+    ))(i)?;
+    let (min, max, expected) = match size {
+        Some(TypeSize16::B8) => (i8::MIN as i64, i8::MAX as i64, "Enum8 value from -128 to 127"),
+        _ => (i16::MIN as i64, i16::MAX as i64, "Enum16 value from -32768 to 32767"),
+    };
+    let value = map_res(
+        recognize(pair(opt(alt((tag("-"), tag("+")))), digit1)),
+        |v| str::from_utf8(v).unwrap().parse::<i64>()
+    );
+    // each item is kept with its position for the errors found after parsing
+    let item = |i| {
+        let (rest, item) = pair(
+            map(raw_string_single_quoted, |name| String::from_utf8(name).unwrap()),
+            opt(preceded(
+                tuple((multispace0, tag("="), multispace0)),
+                cut(context(expected, verify(&value, |v| (min..=max).contains(v)))),
+            )),
+        )(i)?;
+        Ok((rest, (i, item)))
+    };
+    let (rest, items) = terminated(
+        separated_nonempty_list(ws_sep_comma, item),
+        pair(multispace0, tag(")")),
+    )(i)?;
+
+    let failure = |input, expected| NomErr::Failure(SyntaxError { input, kind: ErrorKind::Verify, expected: Some(expected) });
+    let mut values: Vec<(String, i16)> = Vec::with_capacity(items.len());
+    let numbered = items[0].1.1.is_some();
+    let mut next = 1;
+    for (position, (name, value)) in items {
+        if value.is_some() != numbered {
+            return Err(failure(position, "Enum items either all numbered or all unnumbered"));
+        }
+        let value = value.unwrap_or(next);
+        if value > max {
+            return Err(failure(position, expected));
+        }
+        if values.iter().any(|(n, _)| *n == name) {
+            return Err(failure(position, "unique Enum name"));
+        }
+        if values.iter().any(|(_, v)| i64::from(*v) == value) {
+            return Err(failure(position, "unique Enum value"));
+        }
+        values.push((name, value as i16));
+        next = value + 1;
+    }
+    Ok((rest, SqlType::Enum(size, values)))
+}
+
+// Nullable(T), LowCardinality(T), LowCardinality(Nullable(T))
+fn wrapper_type(i: &[u8]) -> IResult<&[u8], SqlType> {
+    let wrapped = |name: &'static str, expected: &'static str, valid: fn(&SqlType) -> bool| {
+        delimited(
+            tuple((tag_no_case(name), multispace0, tag("("), multispace0)),
+            cut(context(expected, verify(context("column type", type_identifier), valid))),
+            pair(multispace0, tag(")")),
+        )
+    };
+    alt((
+        map(
+            wrapped("nullable", "type allowed inside Nullable", SqlType::can_be_nullable),
+            |t| SqlType::Nullable(Box::new(t))
+        ),
+        map(
+            wrapped("lowcardinality", "type allowed inside LowCardinality", SqlType::can_be_low_cardinality),
+            |t| SqlType::LowCardinality(Box::new(t))
+        ),
+    ))(i)
+}
+
+// A SQL type specifier.
+fn type_identifier(i: &[u8]) -> IResult<&[u8], SqlType> {
+    alt((
+        wrapper_type,
+        map(
+            tuple((
+                    tag_no_case("int"),
+                    type_size_suffix,
+            )),
+            |t| SqlType::Int(t.1)
+        ),
+        map(
+            tuple((
+                    tag_no_case("uint"),
+                    type_size_suffix,
+            )),
+            |t| SqlType::UnsignedInt(t.1)
+        ),
+        enum_type,
+        map(tag_no_case("string"), |_| SqlType::String),
+        map(tag_no_case("float32"), |_| SqlType::Float32),
+        map(tag_no_case("float64"), |_| SqlType::Float64),
+        date_type,
         map(
             preceded(
                 tag_no_case("FixedString"),
-                delim_digit,
+                delimited(
+                    tuple((multispace0, tag("("), multispace0)),
+                    cut(context(
+                        "FixedString size",
+                        map_res(digit1, |d| str::from_utf8(d).unwrap().parse::<usize>()),
+                    )),
+                    pair(multispace0, tag(")")),
+                ),
             ),
-            |d| SqlType::FixedString(usize::from_str(str::from_utf8(d).unwrap()).unwrap())
+            SqlType::FixedString
         ),
         map(tag_no_case("ipv4"), |_| SqlType::IPv4),
         map(tag_no_case("ipv6"), |_| SqlType::IPv6),
+        map(type_name("uuid"), |_| SqlType::UUID),
+        map(type_name("bool"), |_| SqlType::Bool),
+        decimal_type,
+        composite_type,
+        aggregate_function_type,
+        special_type,
+        sql_alias_type,
     ))(i)
 }
 
+fn sql_statement(i: &[u8]) -> IResult<&[u8], SqlQuery> {
+    context("statement", alt((
+        map(creation, SqlQuery::CreateTable),
+        map(materialized_view_creation, SqlQuery::CreateMaterializedView),
+        map(view_creation, SqlQuery::CreateView),
+        map(live_view_creation, SqlQuery::CreateLiveView),
+        map(window_view_creation, SqlQuery::CreateWindowView),
+        map(database_creation, SqlQuery::CreateDatabase),
+        map(selection, SqlQuery::Select),
+    )))(i)
+}
+
+/// Parses exactly one statement, optionally followed by `;`.
+///
+/// Anything but whitespace and comments after the statement is an error.
 pub fn sql_query(i: &[u8]) -> IResult<&[u8], SqlQuery> {
-    map(creation, |c| SqlQuery::CreateTable(c))(i)
+    terminated(sql_statement, context("end of statement", terminated(statement_terminator, eof)))(i)
+}
+
+// Length of a statement up to the next `;` outside of quotes and comments.
+fn statement_length(i: &[u8]) -> usize {
+    let mut pos = 0;
+    while pos < i.len() {
+        match i[pos] {
+            b';' => return pos,
+            quote @ b'\'' | quote @ b'"' | quote @ b'`' => {
+                pos += 1;
+                while pos < i.len() && i[pos] != quote {
+                    if i[pos] == b'\\' {
+                        pos += 1;
+                    }
+                    pos += 1;
+                }
+                pos += 1;
+            },
+            _ => match sql_comment(&i[pos..]) {
+                Ok((rest, _)) => pos = i.len() - rest.len(),
+                Err(_) => pos += 1,
+            },
+        }
+    }
+    i.len()
+}
+
+// Skips the rest of a statement up to and including the next `;`.
+fn skip_statement(i: &[u8]) -> &[u8] {
+    &i[(statement_length(i) + 1).min(i.len())..]
+}
+
+/// Parses a whole script of `;`-separated statements.
+///
+/// Comments and empty statements between statements are skipped. A statement
+/// which fails to parse is reported and skipped up to the next `;`, so all
+/// errors of the script are returned in one pass.
+pub fn parse_statements<T>(input: T) -> Vec<Result<SqlQuery, ParseError>>
+where
+    T: AsRef<str>,
+{
+    let source = input.as_ref().as_bytes();
+    let mut queries = Vec::new();
+    let mut rest = source;
+    loop {
+        let (after_comments, _) = many0(alt((multispace1, sql_comment, tag(";"))))(rest)
+            .unwrap_or((rest, Vec::new()));
+        rest = after_comments;
+        if rest.is_empty() {
+            break;
+        }
+
+        match terminated(sql_statement, context("end of statement", statement_terminator))(rest) {
+            Ok((remaining, query)) => {
+                queries.push(Ok(query));
+                rest = remaining;
+            },
+            Err(e) => {
+                queries.push(Err(ParseError::from_nom(source, e)));
+                rest = skip_statement(rest);
+            },
+        }
+    }
+    queries
 }
 
-pub fn parse_query_bytes<T>(input: T) -> Result<SqlQuery, &'static str>
+pub fn parse_query_bytes<T>(input: T) -> Result<SqlQuery, ParseError>
 where
     T: AsRef<[u8]>,
 {
-    match sql_query(input.as_ref()) {
+    let input = input.as_ref();
+    match sql_query(input) {
         Ok((_, o)) => Ok(o),
-        Err(_) => Err("failed to parse query"),
+        Err(e) => Err(ParseError::from_nom(input, e)),
     }
 }
 
-pub fn parse_query<T>(input: T) -> Result<SqlQuery, &'static str>
+pub fn parse_query<T>(input: T) -> Result<SqlQuery, ParseError>
 where
     T: AsRef<str>,
 {
-    parse_query_bytes(input.as_ref().trim().as_bytes())
+    let input = input.as_ref();
+    // positions are reported against the untrimmed input
+    match sql_query(input.trim().as_bytes()) {
+        Ok((_, o)) => Ok(o),
+        Err(e) => Err(ParseError::from_nom(input.as_bytes(), e)),
+    }
 }
 
 #[cfg(test)]
-fn parse_set_for_test<'a, T, F>(f: F, patterns: Vec<(&'a str, T)>)
+fn parse_set_for_test<T, F>(f: F, patterns: Vec<(&str, T)>)
     where
         T: std::fmt::Display + PartialEq,
         F: Fn(&[u8]) -> IResult<&[u8], T>
@@ -527,6 +986,26 @@ mod test {
                 "Enum8('a' = 1, 'b' = 2)",
                 SqlType::Enum(Some(TypeSize16::B8), vec![("a".into(), 1), ("b".into(), 2)])
             ),
+            (
+                "Enum8('a' = -128,'b'=127)",
+                SqlType::Enum(Some(TypeSize16::B8), vec![("a".into(), -128), ("b".into(), 127)])
+            ),
+            (
+                "Enum16( 'it\\'s' = -1000, 'a''b' = 30000 )",
+                SqlType::Enum(Some(TypeSize16::B16), vec![("it's".into(), -1000), ("a'b".into(), 30000)])
+            ),
+            (
+                "Enum('a', 'b')",
+                SqlType::Enum(None, vec![("a".into(), 1), ("b".into(), 2)])
+            ),
+            (
+                "Enum8('a' = +1, 'b' = -1)",
+                SqlType::Enum(Some(TypeSize16::B8), vec![("a".into(), 1), ("b".into(), -1)])
+            ),
+            (
+                "Enum8('a' = 5, 'b' = 6, 'c' = -1, 'd' = 0)",
+                SqlType::Enum(Some(TypeSize16::B8), vec![("a".into(), 5), ("b".into(), 6), ("c".into(), -1), ("d".into(), 0)])
+            ),
             ( "String", SqlType::String ),
             ( "Float32", SqlType::Float32 ),
             ( "Float64", SqlType::Float64 ),
@@ -536,49 +1015,216 @@ mod test {
             ( "DateTime ( 'Cont/City')", SqlType::DateTime(Some("Cont/City".into())) ),
 
             ( "FixedString(3)", SqlType::FixedString(3) ),
+
+            ( "Int128", SqlType::Int(TypeSize::B128) ),
+            ( "UInt256", SqlType::UnsignedInt(TypeSize::B256) ),
+            ( "Date32", SqlType::Date32 ),
+            ( "Date", SqlType::Date ),
+            ( "DateTime64(3)", SqlType::DateTime64(3, None) ),
+            ( "DateTime64(6, 'Europe/Moscow')", SqlType::DateTime64(6, Some("Europe/Moscow".into())) ),
+            ( "UUID", SqlType::UUID ),
+            ( "Bool", SqlType::Bool ),
+            ( "BOOLEAN", SqlType::Bool ),
+            ( "BIGINT", SqlType::Int(TypeSize::B64) ),
+            ( "int", SqlType::Int(TypeSize::B32) ),
+            ( "INTEGER UNSIGNED", SqlType::UnsignedInt(TypeSize::B32) ),
+            ( "TinyInt", SqlType::Int(TypeSize::B8) ),
+            ( "VARCHAR(255)", SqlType::String ),
+            ( "text", SqlType::String ),
+            ( "FLOAT", SqlType::Float32 ),
+            ( "Double", SqlType::Float64 ),
+            ( "DOUBLE PRECISION", SqlType::Float64 ),
+
+            (
+                "AggregateFunction(uniq, UInt64)",
+                SqlType::AggregateFunction {
+                    function: "uniq".into(),
+                    params: None,
+                    args: vec![SqlType::UnsignedInt(TypeSize::B64)],
+                }
+            ),
+            (
+                "AggregateFunction(quantiles(0.5, 0.9), Float64)",
+                SqlType::AggregateFunction {
+                    function: "quantiles".into(),
+                    params: Some(vec![Literal::Float("0.5".into()), Literal::Float("0.9".into())]),
+                    args: vec![SqlType::Float64],
+                }
+            ),
+            (
+                "SimpleAggregateFunction(max, DateTime)",
+                SqlType::SimpleAggregateFunction {
+                    function: "max".into(),
+                    params: None,
+                    args: vec![SqlType::DateTime(None)],
+                }
+            ),
+            ( "Point", SqlType::Point ),
+            ( "MultiPolygon", SqlType::MultiPolygon ),
+            ( "Object('json')", SqlType::Object("json".into()) ),
+            ( "JSON", SqlType::JSON ),
+            ( "Array(Nothing)", SqlType::Array(Box::new(SqlType::Nothing)) ),
+            ( "IntervalDay", SqlType::Interval(IntervalUnit::Day) ),
+            ( "Decimal(18, 4)", SqlType::Decimal { precision: 18, scale: 4 } ),
+            ( "Decimal( 76,76 )", SqlType::Decimal { precision: 76, scale: 76 } ),
+            ( "Decimal(5)", SqlType::Decimal { precision: 5, scale: 0 } ),
+            ( "Decimal32(2)", SqlType::Decimal { precision: 9, scale: 2 } ),
+            ( "Decimal64(4)", SqlType::Decimal { precision: 18, scale: 4 } ),
+            ( "decimal128(38)", SqlType::Decimal { precision: 38, scale: 38 } ),
+            ( "Decimal256(0)", SqlType::Decimal { precision: 76, scale: 0 } ),
+
+            ( "Array(String)", SqlType::Array(Box::new(SqlType::String)) ),
+            (
+                "Tuple(String, UInt8)",
+                SqlType::Tuple(vec![(None, SqlType::String), (None, SqlType::UnsignedInt(TypeSize::B8))])
+            ),
+            (
+                "Tuple(a String, `b c` DateTime('UTC'))",
+                SqlType::Tuple(vec![
+                    (Some("a".into()), SqlType::String),
+                    (Some("b c".into()), SqlType::DateTime(Some("UTC".into()))),
+                ])
+            ),
+            (
+                "Map(String, UInt64)",
+                SqlType::Map(Box::new(SqlType::String), Box::new(SqlType::UnsignedInt(TypeSize::B64)))
+            ),
+            (
+                "Nested(id UInt32, names Array(String))",
+                SqlType::Nested(vec![
+                    ("id".into(), SqlType::UnsignedInt(TypeSize::B32)),
+                    ("names".into(), SqlType::Array(Box::new(SqlType::String))),
+                ])
+            ),
         ];
         parse_set_for_test(type_identifier, patterns);
     }
- 
+
     #[test]
-    fn t_sql_expression() {
+    fn t_type_identifier_display() {
         let patterns = vec![
-            ( "rand()", "rand()".to_string() ),
-            ( "toDate(requestedAt)", "toDate(requestedAt)".to_string() ),
-            ( "(col1, coln2, rand())", "(col1, coln2, rand())".to_string() ),
-            ( "func(col)", "func(col)".to_string() ),
-            ( "func('col')", "func('col')".to_string() ),
-            ( "func('col','df')", "func('col','df')".to_string() ),
-            ( "cast('val' as Date)", "cast('val' as Date)".to_string() ),
-            (
-                r#"CAST('captcha', 'Enum8(\'captcha\' = 1, \'ban\' = 2)')"#,
-                r#"CAST('captcha', 'Enum8(\'captcha\' = 1, \'ban\' = 2)')"#.to_string()
-            ),
-            ( "z>1", "z>1".to_string() ),
+            ( "Array(Array(Int8))", "Array(Array(Int8))".to_string() ),
+            ( "array( tuple(String,Float64) )", "Array(Tuple(String, Float64))".to_string() ),
+            ( "Map(String, Map(String, Array(Tuple(x Int32, y Int32))))", "Map(String, Map(String, Array(Tuple(x Int32, y Int32))))".to_string() ),
+            ( "Tuple(String)", "Tuple(String)".to_string() ),
+            ( "Tuple()", "Tuple()".to_string() ),
+            ( "Array(tuple( ))", "Array(Tuple())".to_string() ),
+            ( "DateTime('UTC')", "DateTime('UTC')".to_string() ),
+            ( "DateTime( 'Europe/Kyiv' )", "DateTime('Europe/Kyiv')".to_string() ),
+            ( "DateTime('it\\'s')", "DateTime('it\\'s')".to_string() ),
+            ( "DateTime('a''b')", "DateTime('a\\'b')".to_string() ),
+            ( "datetime64( 3,'UTC' )", "DateTime64(3, 'UTC')".to_string() ),
+            ( "Array(Nullable(BIGINT UNSIGNED))", "Array(Nullable(UInt64))".to_string() ),
+            ( "LowCardinality(VARCHAR)", "LowCardinality(String)".to_string() ),
+            ( "AggregateFunction(count)", "AggregateFunction(count)".to_string() ),
+            ( "Enum('x', 'y')", "Enum('x' = 1, 'y' = 2)".to_string() ),
+            ( "Enum8('it\\'s' = -1, 'a''b' = 2)", "Enum8('it\\'s' = -1, 'a\\'b' = 2)".to_string() ),
+            ( "Tuple(p Point, r Ring, l LineString)", "Tuple(p Point, r Ring, l LineString)".to_string() ),
+            ( "Array(Polygon)", "Array(Polygon)".to_string() ),
+            ( "MultiLineString", "MultiLineString".to_string() ),
+            ( "object( 'json' )", "Object('json')".to_string() ),
+            ( "Nullable(Nothing)", "Nullable(Nothing)".to_string() ),
+            ( "intervalmillisecond", "IntervalMillisecond".to_string() ),
+            ( "Map(String, IntervalQuarter)", "Map(String, IntervalQuarter)".to_string() ),
+            ( "AggregateFunction( any , String )", "AggregateFunction(any, String)".to_string() ),
             (
-                "assumeNotNull(if(1>1, murmurHash3_64(d), rand()))",
-                "assumeNotNull(if(1>1, murmurHash3_64(d), rand()))".to_string()
+                "AggregateFunction(sequenceMatch('(?1)(?2)'), DateTime, UInt8, UInt8)",
+                "AggregateFunction(sequenceMatch('(?1)(?2)'), DateTime, UInt8, UInt8)".to_string()
             ),
+            ( "AggregateFunction(topK(10), Nullable(String))", "AggregateFunction(topK(10), Nullable(String))".to_string() ),
             (
-                "assumeNotNull(if(length(deviceId) > 1, murmurHash3_64(deviceId), rand()))",
-                "assumeNotNull(if(length(deviceId) > 1, murmurHash3_64(deviceId), rand()))".to_string()
+                "SimpleAggregateFunction(groupUniqArrayArray, Array(String))",
+                "SimpleAggregateFunction(groupUniqArrayArray, Array(String))".to_string()
             ),
+            ( "AggregateFunction(quantilesIf(0.5), Float64, UInt8)", "AggregateFunction(quantilesIf(0.5), Float64, UInt8)".to_string() ),
+            ( "Tuple(`from` Date, to Date)", "Tuple(`from` Date, `to` Date)".to_string() ),
+            ( "Nested(a Decimal32(2), b Nested(c Enum8('x' = 1)))", "Nested(a Decimal(9, 2), b Nested(c Enum8('x' = 1)))".to_string() ),
         ];
-        parse_set_for_test(|i| sql_expression(i)
-                .map(|(_, o)| ("".as_bytes(), str::from_utf8(o).unwrap().to_string())),
+        parse_set_for_test(|i| type_identifier(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
             patterns);
     }
+ 
+    fn type_error(sql_type: &str) -> ParseError {
+        ParseError::from_nom(sql_type.as_bytes(), type_identifier(sql_type.as_bytes()).unwrap_err())
+    }
 
     #[test]
-    fn t_ttl_expression() {
-        let patterns = vec![
-            ( "col", "col".to_string() ),
-            ( "col INTERVAL + 1 day", "col INTERVAL + 1 day".to_string() ),
-            ( "col INTERVAL - 15 year", "col INTERVAL - 15 year".to_string() ),
-        ];
-        parse_set_for_test(|i| ttl_expression(i)
-                .map(|(_, o)| ("".as_bytes(), str::from_utf8(o).unwrap().to_string())),
-            patterns);
+    fn t_type_identifier_decimal() {
+        let err = type_error("Decimal(77, 2)");
+        assert_eq!(err.expected, "Decimal precision from 1 to 76");
+        assert_eq!(err.found, "`77`");
+
+        let err = type_error("Decimal(10, 11)");
+        assert_eq!(err.expected, "Decimal scale not greater than precision");
+        assert_eq!(err.column, 13);
+
+        assert_eq!(type_error("Decimal64(19)").expected, "Decimal64 scale from 0 to 18");
+    }
+
+    #[test]
+    fn t_type_identifier_fixed_string() {
+        let err = type_error("FixedString(99999999999999999999999)");
+        assert_eq!(err.expected, "FixedString size");
+        assert_eq!(err.column, 13);
+    }
+
+    #[test]
+    fn t_type_identifier_enum() {
+        let err = type_error("Enum8('a' = 128)");
+        assert_eq!(err.expected, "Enum8 value from -128 to 127");
+        assert_eq!(err.found, "`128`");
+
+        assert_eq!(type_error("Enum16('a' = 99999999999999999999)").expected, "Enum16 value from -32768 to 32767");
+        // auto numbering past 127, reported on the 128th item
+        let names = (0..128).map(|n| format!("'e{}'", n)).collect::<Vec<String>>();
+        let err = type_error(&format!("Enum8({})", names.join(", ")));
+        assert_eq!(err.expected, "Enum8 value from -128 to 127");
+        assert_eq!(err.found, "`'e127'`");
+
+        let err = type_error("Enum8('a', 'b' = 5, 'c')");
+        assert_eq!(err.expected, "Enum items either all numbered or all unnumbered");
+        assert_eq!(err.found, "`'b'`");
+        assert_eq!(type_error("Enum8('a' = 1, 'b')").found, "`'b'`");
+
+        let err = type_error("Enum8('a' = 1, 'b' = 1)");
+        assert_eq!(err.expected, "unique Enum value");
+        assert_eq!(err.found, "`'b'`");
+        assert_eq!(type_error("Enum8('a' = 1, 'a' = 2)").expected, "unique Enum name");
+        assert_eq!(type_error("Enum('a', 'a')").expected, "unique Enum name");
+    }
+
+    #[test]
+    fn t_type_identifier_wrapper() {
+        for t in &[
+            "Nullable(AggregateFunction(count))",
+            "Nullable(SimpleAggregateFunction(sum, UInt64))",
+            "Nullable(Point)",
+            "Nullable(Ring)",
+            "Nullable(Polygon)",
+            "Nullable(MultiPolygon)",
+            "Nullable(Object('json'))",
+            "Nullable(JSON)",
+        ] {
+            assert_eq!(type_error(t).expected, "type allowed inside Nullable", "{}", t);
+        }
+        for t in &[
+            "LowCardinality(Nothing)",
+            "LowCardinality(Nullable(Nothing))",
+            "LowCardinality(Point)",
+            "LowCardinality(Map(String, UInt8))",
+        ] {
+            assert_eq!(type_error(t).expected, "type allowed inside LowCardinality", "{}", t);
+        }
+
+        let err = type_error("Nullable(Array(String))");
+        assert_eq!(err.expected, "type allowed inside Nullable");
+        assert_eq!(err.found, "`Array`");
+        assert_eq!(type_error("Nullable(LowCardinality(String))").expected, "type allowed inside Nullable");
+
+        let err = type_error("Nullable(Strang)");
+        assert_eq!(err.expected, "column type");
+        assert_eq!(err.found, "`Strang`");
     }
 
     #[test]
@@ -616,11 +1262,65 @@ mod test {
     }
 
     #[test]
-    fn t_sql_identifier_incorrect() {
-        match sql_identifier(r#"'.inner.api_path_time_view'"#.as_bytes()) {
-            Ok(_) => assert!(false),
-            Err(_) => assert!(true),
+    fn t_comments_inside_statement() {
+        let sql = "CREATE TABLE t (
+            -- the id
+            `id` UInt64, # primary key
+            /* the name */ `name` String
+        ) ENGINE = MergeTree /* engine */ ORDER BY id -- key
+        ;";
+        let exp = "CREATE TABLE t (\n  `id` UInt64,\n  `name` String\n) ENGINE = MergeTree ORDER BY id;";
+        assert_eq!(exp, parse_query(sql).unwrap().to_string());
+
+        let res = parse_statements("SELECT a -- first\n, b FROM t; SELECT /* all */ * FROM t");
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].as_ref().unwrap().to_string(), "SELECT a, b FROM t;");
+        assert_eq!(res[1].as_ref().unwrap().to_string(), "SELECT * FROM t;");
+    }
+
+    #[test]
+    fn t_parse_statements() {
+        let script = r#"
+            -- engine is not set; this one fails
+            CREATE TABLE a (`s` String DEFAULT ';');
+            # comment with a ; inside
+            CREATE TABLE b (`s` String DEFAULT 'x;y') ENGINE = Memory;;
+            /* multi-line;
+               comment */
+            CREATE TABLE c (
+                `i` UInt8
+            ) ENGINE = Memory /* trailing */
+            ;
+            CREATE TABLE d (`i` UInt8) ENGINE = Memory PARTITION BY i;
+            CREATE TABLE e (`i` UInt8) ENGINE = Memory
+        "#;
+        let res = parse_statements(script);
+        assert_eq!(res.len(), 5);
+
+        let err = res[0].as_ref().unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.expected, "ENGINE clause");
+
+        match res[1] {
+            Ok(SqlQuery::CreateTable(ref t)) => assert_eq!(t.table.name, "b"),
+            ref r => panic!("unexpected {:?}", r),
+        }
+        match res[2] {
+            Ok(SqlQuery::CreateTable(ref t)) => assert_eq!(t.table.name, "c"),
+            ref r => panic!("unexpected {:?}", r),
         }
+
+        let err = res[3].as_ref().unwrap_err();
+        assert_eq!(err.line, 12);
+        assert_eq!(err.expected, "end of statement");
+        assert_eq!(err.found, "`PARTITION`");
+
+        assert!(res[4].is_ok());
+    }
+
+    #[test]
+    fn t_sql_identifier_incorrect() {
+        assert!(sql_identifier(r#"'.inner.api_path_time_view'"#.as_bytes()).is_err());
     }
 
 }
@@ -0,0 +1,1162 @@
+// vim: set expandtab ts=4 sw=4:
+use std::str;
+use std::str::FromStr;
+use std::fmt;
+
+use nom::{
+    error::context,
+    branch::alt,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    combinator::{map, map_res, not, opt, peek, recognize, verify},
+    character::complete::digit1,
+    bytes::complete::{tag, tag_no_case, take_while1},
+    multi::{fold_many0, many0, separated_nonempty_list},
+};
+
+use crate::{
+    IResult,
+    multispace0,
+    multispace1,
+    escape_identifier,
+    escape_string,
+    is_sql_identifier,
+    raw_string_single_quoted,
+    schema_table_reference_no_alias,
+    sql_identifier,
+    ws_sep_comma,
+};
+use crate::expression::{
+    Expr,
+    Function,
+    expression,
+    function_call,
+};
+use crate::table::Table;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SelectStatement {
+    pub with: Vec<WithItem>,
+    pub distinct: bool,
+    pub fields: Vec<FieldDefinitionExpression>,
+    pub from: Option<FromClause>,
+    pub array_join: Option<ArrayJoinClause>,
+    pub joins: Vec<JoinClause>,
+    pub prewhere: Option<Expr>,
+    pub where_clause: Option<Expr>,
+    pub group_by: Option<GroupByClause>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<OrderByItem>,
+    pub limit_by: Option<LimitByClause>,
+    pub limit: Option<LimitClause>,
+    pub settings: Vec<(String, Expr)>,
+    pub format: Option<String>,
+}
+
+impl fmt::Display for SelectStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.with.is_empty() {
+            write!(f, "WITH {} ", join(&self.with))?;
+        }
+        write!(f, "SELECT ")?;
+        if self.distinct {
+            write!(f, "DISTINCT ")?;
+        }
+        write!(f, "{}", join(&self.fields))?;
+        if let Some(ref from) = self.from {
+            write!(f, " FROM {}", from)?;
+        }
+        if let Some(ref array_join) = self.array_join {
+            write!(f, " {}", array_join)?;
+        }
+        for j in self.joins.iter() {
+            write!(f, " {}", j)?;
+        }
+        if let Some(ref prewhere) = self.prewhere {
+            write!(f, " PREWHERE {}", prewhere)?;
+        }
+        if let Some(ref where_clause) = self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        if let Some(ref group_by) = self.group_by {
+            write!(f, " {}", group_by)?;
+        }
+        if let Some(ref having) = self.having {
+            write!(f, " HAVING {}", having)?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, " ORDER BY {}", join(&self.order_by))?;
+        }
+        if let Some(ref limit_by) = self.limit_by {
+            write!(f, " {}", limit_by)?;
+        }
+        if let Some(ref limit) = self.limit {
+            write!(f, " {}", limit)?;
+        }
+        if !self.settings.is_empty() {
+            write!(f, " SETTINGS {}",
+                self.settings
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        if let Some(ref format) = self.format {
+            write!(f, " FORMAT {}", format)?;
+        }
+        Ok(())
+    }
+}
+
+// SELECT, possibly combined with other queries by set operations
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SelectQuery {
+    Select(Box<SelectStatement>),
+    // (query)
+    Nested(Box<SelectQuery>),
+    Compound(Box<CompoundSelectStatement>),
+}
+
+impl fmt::Display for SelectQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectQuery::Select(select) => write!(f, "{}", select),
+            SelectQuery::Nested(query) => write!(f, "({})", query),
+            SelectQuery::Compound(compound) => write!(f, "{}", compound),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SetOperator {
+    // bare UNION, its meaning depends on the `union_default_mode` setting
+    Union,
+    UnionAll,
+    UnionDistinct,
+    Except,
+    Intersect,
+}
+
+impl fmt::Display for SetOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SetOperator::Union => write!(f, "UNION"),
+            SetOperator::UnionAll => write!(f, "UNION ALL"),
+            SetOperator::UnionDistinct => write!(f, "UNION DISTINCT"),
+            SetOperator::Except => write!(f, "EXCEPT"),
+            SetOperator::Intersect => write!(f, "INTERSECT"),
+        }
+    }
+}
+
+// left operator right
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CompoundSelectStatement {
+    pub left: SelectQuery,
+    pub operator: SetOperator,
+    pub right: SelectQuery,
+}
+
+impl fmt::Display for CompoundSelectStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.operator, self.right)
+    }
+}
+
+// WITH items: `expr AS name` or `name AS (subquery)`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum WithItem {
+    Expression {
+        expr: Expr,
+        alias: String,
+    },
+    Subquery {
+        name: String,
+        query: Box<SelectQuery>,
+    },
+}
+
+impl fmt::Display for WithItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WithItem::Expression { expr, alias } => write!(f, "{} AS {}", expr, escape_identifier(alias)),
+            WithItem::Subquery { name, query } => write!(f, "{} AS ({})", escape_identifier(name), query),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FieldDefinitionExpression {
+    // *
+    All,
+    // table.*
+    AllInTable(String),
+    Expression {
+        expr: Expr,
+        alias: Option<String>,
+    },
+}
+
+impl fmt::Display for FieldDefinitionExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldDefinitionExpression::All => write!(f, "*"),
+            FieldDefinitionExpression::AllInTable(table) => write!(f, "{}.*", escape_identifier(table)),
+            FieldDefinitionExpression::Expression { expr, alias } => {
+                write!(f, "{}", expr)?;
+                if let Some(ref alias) = alias {
+                    write!(f, " AS {}", escape_identifier(alias))?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TableExpression {
+    Table(Table),
+    Subquery(Box<SelectQuery>, Option<String>),
+    // numbers(10), remote('host', db, table), ...
+    Function(Function, Option<String>),
+}
+
+impl fmt::Display for TableExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let alias = match self {
+            TableExpression::Table(table) => return write!(f, "{}", table),
+            TableExpression::Subquery(query, alias) => {
+                write!(f, "({})", query)?;
+                alias
+            },
+            TableExpression::Function(function, alias) => {
+                write!(f, "{}", function)?;
+                alias
+            },
+        };
+        if let Some(ref alias) = alias {
+            write!(f, " AS {}", escape_identifier(alias))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FromClause {
+    pub source: TableExpression,
+    pub is_final: bool,
+    pub sample: Option<SampleClause>,
+}
+
+impl fmt::Display for FromClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)?;
+        if self.is_final {
+            write!(f, " FINAL")?;
+        }
+        if let Some(ref sample) = self.sample {
+            write!(f, " {}", sample)?;
+        }
+        Ok(())
+    }
+}
+
+// SAMPLE 0.1, SAMPLE 1/10 OFFSET 1/2, SAMPLE 10000
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SampleClause {
+    pub ratio: String,
+    pub offset: Option<String>,
+}
+
+impl fmt::Display for SampleClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SAMPLE {}", self.ratio)?;
+        if let Some(ref offset) = self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ArrayJoinClause {
+    pub left: bool,
+    pub arrays: Vec<(Expr, Option<String>)>,
+}
+
+impl fmt::Display for ArrayJoinClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.left {
+            write!(f, "LEFT ")?;
+        }
+        write!(f, "ARRAY JOIN {}",
+            self.arrays
+                .iter()
+                .map(|(expr, alias)| match alias {
+                    Some(alias) => format!("{} AS {}", expr, escape_identifier(alias)),
+                    None => format!("{}", expr),
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross,
+}
+
+impl fmt::Display for JoinKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JoinKind::Inner => write!(f, "INNER"),
+            JoinKind::Left => write!(f, "LEFT"),
+            JoinKind::Right => write!(f, "RIGHT"),
+            JoinKind::Full => write!(f, "FULL"),
+            JoinKind::Cross => write!(f, "CROSS"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum JoinStrictness {
+    All,
+    Any,
+    Asof,
+    Semi,
+    Anti,
+}
+
+impl fmt::Display for JoinStrictness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JoinStrictness::All => write!(f, "ALL"),
+            JoinStrictness::Any => write!(f, "ANY"),
+            JoinStrictness::Asof => write!(f, "ASOF"),
+            JoinStrictness::Semi => write!(f, "SEMI"),
+            JoinStrictness::Anti => write!(f, "ANTI"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum JoinConstraint {
+    On(Expr),
+    Using(Vec<String>),
+}
+
+impl fmt::Display for JoinConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JoinConstraint::On(expr) => write!(f, "ON {}", expr),
+            JoinConstraint::Using(columns) => write!(f, "USING ({})",
+                columns
+                    .iter()
+                    .map(|c| escape_identifier(c))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct JoinClause {
+    pub global: bool,
+    pub strictness: Option<JoinStrictness>,
+    // plain JOIN is an INNER JOIN
+    pub kind: JoinKind,
+    pub table: TableExpression,
+    // CROSS JOIN has no constraint
+    pub constraint: Option<JoinConstraint>,
+}
+
+impl fmt::Display for JoinClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.global {
+            write!(f, "GLOBAL ")?;
+        }
+        if let Some(ref strictness) = self.strictness {
+            write!(f, "{} ", strictness)?;
+        }
+        write!(f, "{} JOIN {}", self.kind, self.table)?;
+        if let Some(ref constraint) = self.constraint {
+            write!(f, " {}", constraint)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GroupByModifier {
+    Rollup,
+    Cube,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct GroupByClause {
+    pub columns: Vec<Expr>,
+    pub modifier: Option<GroupByModifier>,
+    pub with_totals: bool,
+}
+
+impl fmt::Display for GroupByClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GROUP BY {}", join(&self.columns))?;
+        match self.modifier {
+            Some(GroupByModifier::Rollup) => write!(f, " WITH ROLLUP")?,
+            Some(GroupByModifier::Cube) => write!(f, " WITH CUBE")?,
+            None => (),
+        }
+        if self.with_totals {
+            write!(f, " WITH TOTALS")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum OrderType {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+// ORDER BY ... WITH FILL [FROM expr] [TO expr] [STEP expr]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct WithFill {
+    pub from: Option<Expr>,
+    pub to: Option<Expr>,
+    pub step: Option<Expr>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OrderByItem {
+    pub expr: Expr,
+    pub order: Option<OrderType>,
+    pub nulls: Option<NullsOrder>,
+    pub collate: Option<String>,
+    pub fill: Option<WithFill>,
+}
+
+impl fmt::Display for OrderByItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        match self.order {
+            Some(OrderType::Asc) => write!(f, " ASC")?,
+            Some(OrderType::Desc) => write!(f, " DESC")?,
+            None => (),
+        }
+        match self.nulls {
+            Some(NullsOrder::First) => write!(f, " NULLS FIRST")?,
+            Some(NullsOrder::Last) => write!(f, " NULLS LAST")?,
+            None => (),
+        }
+        if let Some(ref collate) = self.collate {
+            write!(f, " COLLATE {}", escape_string(collate))?;
+        }
+        if let Some(ref fill) = self.fill {
+            write!(f, " WITH FILL")?;
+            if let Some(ref from) = fill.from {
+                write!(f, " FROM {}", from)?;
+            }
+            if let Some(ref to) = fill.to {
+                write!(f, " TO {}", to)?;
+            }
+            if let Some(ref step) = fill.step {
+                write!(f, " STEP {}", step)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// LIMIT n [OFFSET m] BY columns
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LimitByClause {
+    pub limit: u64,
+    pub offset: Option<u64>,
+    pub columns: Vec<Expr>,
+}
+
+impl fmt::Display for LimitByClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LIMIT {}", self.limit)?;
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        write!(f, " BY {}", join(&self.columns))
+    }
+}
+
+// LIMIT n [OFFSET m] [WITH TIES], `LIMIT m, n` is read as `LIMIT n OFFSET m`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LimitClause {
+    pub limit: u64,
+    pub offset: Option<u64>,
+    pub with_ties: bool,
+}
+
+impl fmt::Display for LimitClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LIMIT {}", self.limit)?;
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        if self.with_ties {
+            write!(f, " WITH TIES")?;
+        }
+        Ok(())
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|i| format!("{}", i))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Words which may follow a table or a column and must not be taken for
+// its alias. Not all of them are reserved in keywords.rs as some are also
+// function names, e.g. `any(x)` or `array(1, 2)`.
+const CLAUSE_WORDS: [&str; 32] = [
+    "all", "anti", "any", "array", "asof", "by", "cross", "except", "final",
+    "format", "from", "full", "global", "group", "having", "inner", "intersect",
+    "join", "left", "limit", "on", "order", "outer", "prewhere", "right",
+    "sample", "semi", "settings", "union", "using", "where", "with",
+];
+
+fn clause_word(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    verify(take_while1(is_sql_identifier), |w: &[u8]| {
+        CLAUSE_WORDS.iter().any(|k| k.as_bytes().eq_ignore_ascii_case(w))
+    })(i)
+}
+
+// [AS] alias, the implicit form can not be a clause word
+fn alias(i: &[u8]) -> IResult<&[u8], String> {
+    map(
+        alt((
+            preceded(tuple((multispace1, tag_no_case("as"), multispace1)), sql_identifier),
+            preceded(pair(multispace1, not(peek(clause_word))), sql_identifier),
+        )),
+        |a| str::from_utf8(a).unwrap().to_string()
+    )(i)
+}
+
+fn expression_list(i: &[u8]) -> IResult<&[u8], Vec<Expr>> {
+    separated_nonempty_list(ws_sep_comma, expression)(i)
+}
+
+fn unsigned_number(i: &[u8]) -> IResult<&[u8], u64> {
+    map_res(digit1, |d| u64::from_str(str::from_utf8(d).unwrap()))(i)
+}
+
+// space separated keyword followed by whitespace, e.g. ` WHERE `
+fn clause<'a>(keyword: &'static str) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
+    delimited(multispace1, tag_no_case(keyword), multispace1)
+}
+
+fn with_item(i: &[u8]) -> IResult<&[u8], WithItem> {
+    alt((
+        map(
+            tuple((
+                sql_identifier,
+                clause("as"),
+                tag("("),
+                multispace0,
+                nested_selection,
+                multispace0,
+                tag(")"),
+            )),
+            |(name, _, _, _, query, _, _)| WithItem::Subquery {
+                name: str::from_utf8(name).unwrap().to_string(),
+                query: Box::new(query),
+            }
+        ),
+        map(
+            tuple((expression, clause("as"), sql_identifier)),
+            |(expr, _, alias)| WithItem::Expression {
+                expr,
+                alias: str::from_utf8(alias).unwrap().to_string(),
+            }
+        ),
+    ))(i)
+}
+
+fn field_definition_expr(i: &[u8]) -> IResult<&[u8], FieldDefinitionExpression> {
+    alt((
+        map(tag("*"), |_| FieldDefinitionExpression::All),
+        map(
+            terminated(sql_identifier, tag(".*")),
+            |t| FieldDefinitionExpression::AllInTable(str::from_utf8(t).unwrap().to_string())
+        ),
+        map(
+            pair(expression, opt(alias)),
+            |(expr, alias)| FieldDefinitionExpression::Expression { expr, alias }
+        ),
+    ))(i)
+}
+
+pub fn table_expression(i: &[u8]) -> IResult<&[u8], TableExpression> {
+    alt((
+        map(
+            pair(
+                delimited(pair(tag("("), multispace0), nested_selection, pair(multispace0, tag(")"))),
+                opt(alias),
+            ),
+            |(query, alias)| TableExpression::Subquery(Box::new(query), alias)
+        ),
+        map(
+            pair(function_call, opt(alias)),
+            |(function, alias)| TableExpression::Function(function, alias)
+        ),
+        map(
+            pair(schema_table_reference_no_alias, opt(alias)),
+            |(mut table, alias)| {
+                table.alias = alias;
+                TableExpression::Table(table)
+            }
+        ),
+    ))(i)
+}
+
+fn sample_ratio(i: &[u8]) -> IResult<&[u8], String> {
+    map(
+        recognize(tuple((
+            digit1,
+            opt(pair(tag("."), digit1)),
+            opt(tuple((multispace0, tag("/"), multispace0, digit1))),
+        ))),
+        |r| str::from_utf8(r).unwrap().to_string()
+    )(i)
+}
+
+fn from_clause(i: &[u8]) -> IResult<&[u8], FromClause> {
+    map(
+        tuple((
+            preceded(clause("from"), context("table expression", table_expression)),
+            opt(pair(multispace1, tag_no_case("final"))),
+            opt(map(
+                pair(
+                    preceded(clause("sample"), sample_ratio),
+                    opt(preceded(clause("offset"), sample_ratio)),
+                ),
+                |(ratio, offset)| SampleClause { ratio, offset }
+            )),
+        )),
+        |(source, is_final, sample)| FromClause {
+            source,
+            is_final: is_final.is_some(),
+            sample,
+        }
+    )(i)
+}
+
+fn array_join_clause(i: &[u8]) -> IResult<&[u8], ArrayJoinClause> {
+    map(
+        tuple((
+            multispace1,
+            opt(terminated(tag_no_case("left"), multispace1)),
+            tag_no_case("array"),
+            clause("join"),
+            separated_nonempty_list(ws_sep_comma, pair(expression, opt(alias))),
+        )),
+        |(_, left, _, _, arrays)| ArrayJoinClause {
+            left: left.is_some(),
+            arrays,
+        }
+    )(i)
+}
+
+fn join_kind(i: &[u8]) -> IResult<&[u8], JoinKind> {
+    alt((
+        map(tag_no_case("inner"), |_| JoinKind::Inner),
+        map(tag_no_case("left"), |_| JoinKind::Left),
+        map(tag_no_case("right"), |_| JoinKind::Right),
+        map(tag_no_case("full"), |_| JoinKind::Full),
+        map(tag_no_case("cross"), |_| JoinKind::Cross),
+    ))(i)
+}
+
+fn join_strictness(i: &[u8]) -> IResult<&[u8], JoinStrictness> {
+    alt((
+        map(tag_no_case("all"), |_| JoinStrictness::All),
+        map(tag_no_case("any"), |_| JoinStrictness::Any),
+        map(tag_no_case("asof"), |_| JoinStrictness::Asof),
+        map(tag_no_case("semi"), |_| JoinStrictness::Semi),
+        map(tag_no_case("anti"), |_| JoinStrictness::Anti),
+    ))(i)
+}
+
+fn join_constraint(i: &[u8]) -> IResult<&[u8], JoinConstraint> {
+    let column = |i| map(sql_identifier, |c| str::from_utf8(c).unwrap().to_string())(i);
+    alt((
+        map(preceded(clause("on"), expression), JoinConstraint::On),
+        map(
+            preceded(
+                pair(multispace1, tag_no_case("using")),
+                alt((
+                    delimited(
+                        tuple((multispace0, tag("("), multispace0)),
+                        separated_nonempty_list(ws_sep_comma, column),
+                        pair(multispace0, tag(")")),
+                    ),
+                    preceded(multispace1, separated_nonempty_list(ws_sep_comma, column)),
+                )),
+            ),
+            JoinConstraint::Using
+        ),
+    ))(i)
+}
+
+// [GLOBAL] [ANY|ALL|ASOF] [INNER|LEFT|RIGHT|FULL|CROSS] [OUTER|SEMI|ANTI] JOIN table [ON expr|USING columns]
+fn join_clause(i: &[u8]) -> IResult<&[u8], JoinClause> {
+    map(
+        tuple((
+            multispace1,
+            opt(terminated(tag_no_case("global"), multispace1)),
+            opt(terminated(join_strictness, multispace1)),
+            opt(terminated(join_kind, multispace1)),
+            opt(terminated(tag_no_case("outer"), multispace1)),
+            opt(terminated(join_strictness, multispace1)),
+            tag_no_case("join"),
+            multispace1,
+            context("table expression", table_expression),
+            opt(join_constraint),
+        )),
+        |(_, global, strictness, kind, _, strictness_after, _, _, table, constraint)| JoinClause {
+            global: global.is_some(),
+            strictness: strictness.or(strictness_after),
+            kind: kind.unwrap_or(JoinKind::Inner),
+            table,
+            constraint,
+        }
+    )(i)
+}
+
+fn group_by_clause(i: &[u8]) -> IResult<&[u8], GroupByClause> {
+    map(
+        tuple((
+            preceded(pair(clause("group"), tag_no_case("by")), preceded(multispace1, expression_list)),
+            opt(preceded(
+                clause("with"),
+                alt((
+                    map(tag_no_case("rollup"), |_| GroupByModifier::Rollup),
+                    map(tag_no_case("cube"), |_| GroupByModifier::Cube),
+                )),
+            )),
+            opt(pair(clause("with"), tag_no_case("totals"))),
+        )),
+        |(columns, modifier, totals)| GroupByClause {
+            columns,
+            modifier,
+            with_totals: totals.is_some(),
+        }
+    )(i)
+}
+
+fn with_fill(i: &[u8]) -> IResult<&[u8], WithFill> {
+    map(
+        tuple((
+            clause("with"),
+            tag_no_case("fill"),
+            opt(preceded(clause("from"), expression)),
+            opt(preceded(clause("to"), expression)),
+            opt(preceded(clause("step"), expression)),
+        )),
+        |(_, _, from, to, step)| WithFill { from, to, step }
+    )(i)
+}
+
+fn order_by_item(i: &[u8]) -> IResult<&[u8], OrderByItem> {
+    map(
+        tuple((
+            expression,
+            opt(preceded(
+                multispace1,
+                alt((
+                    map(tag_no_case("asc"), |_| OrderType::Asc),
+                    map(tag_no_case("desc"), |_| OrderType::Desc),
+                )),
+            )),
+            opt(preceded(
+                clause("nulls"),
+                alt((
+                    map(tag_no_case("first"), |_| NullsOrder::First),
+                    map(tag_no_case("last"), |_| NullsOrder::Last),
+                )),
+            )),
+            opt(preceded(clause("collate"), raw_string_single_quoted)),
+            opt(with_fill),
+        )),
+        |(expr, order, nulls, collate, fill)| OrderByItem {
+            expr,
+            order,
+            nulls,
+            collate: collate.map(|c| String::from_utf8(c).unwrap()),
+            fill,
+        }
+    )(i)
+}
+
+fn order_by_clause(i: &[u8]) -> IResult<&[u8], Vec<OrderByItem>> {
+    preceded(
+        tuple((clause("order"), tag_no_case("by"), multispace1)),
+        separated_nonempty_list(ws_sep_comma, order_by_item),
+    )(i)
+}
+
+// n [OFFSET m] or m, n
+fn limit_offset(i: &[u8]) -> IResult<&[u8], (u64, Option<u64>)> {
+    alt((
+        map(
+            tuple((unsigned_number, ws_sep_comma, unsigned_number)),
+            |(offset, _, limit)| (limit, Some(offset))
+        ),
+        pair(unsigned_number, opt(preceded(clause("offset"), unsigned_number))),
+    ))(i)
+}
+
+fn limit_by_clause(i: &[u8]) -> IResult<&[u8], LimitByClause> {
+    map(
+        tuple((
+            preceded(clause("limit"), limit_offset),
+            preceded(clause("by"), expression_list),
+        )),
+        |((limit, offset), columns)| LimitByClause { limit, offset, columns }
+    )(i)
+}
+
+fn limit_clause(i: &[u8]) -> IResult<&[u8], LimitClause> {
+    map(
+        pair(
+            preceded(clause("limit"), limit_offset),
+            opt(pair(clause("with"), tag_no_case("ties"))),
+        ),
+        |((limit, offset), ties)| LimitClause {
+            limit,
+            offset,
+            with_ties: ties.is_some(),
+        }
+    )(i)
+}
+
+pub fn setting(i: &[u8]) -> IResult<&[u8], (String, Expr)> {
+    map(
+        tuple((
+            sql_identifier,
+            multispace0,
+            tag("="),
+            multispace0,
+            expression,
+        )),
+        |(name, _, _, _, value)| (str::from_utf8(name).unwrap().to_string(), value)
+    )(i)
+}
+
+fn settings_clause(i: &[u8]) -> IResult<&[u8], Vec<(String, Expr)>> {
+    preceded(
+        clause("settings"),
+        context("setting", separated_nonempty_list(ws_sep_comma, setting)),
+    )(i)
+}
+
+// A single SELECT without set operations
+pub fn select_statement(i: &[u8]) -> IResult<&[u8], SelectStatement> {
+    let (i, with) = opt(terminated(
+        preceded(
+            pair(tag_no_case("with"), multispace1),
+            separated_nonempty_list(ws_sep_comma, with_item),
+        ),
+        multispace1,
+    ))(i)?;
+    let (i, _) = tag_no_case("select")(i)?;
+    let (i, distinct) = opt(preceded(multispace1, tag_no_case("distinct")))(i)?;
+    let (i, fields) = preceded(
+        multispace1,
+        context("column list", separated_nonempty_list(ws_sep_comma, field_definition_expr)),
+    )(i)?;
+    let (i, from) = opt(from_clause)(i)?;
+    let (i, array_join) = opt(array_join_clause)(i)?;
+    let (i, joins) = many0(join_clause)(i)?;
+    let (i, prewhere) = opt(preceded(clause("prewhere"), expression))(i)?;
+    let (i, where_clause) = opt(preceded(clause("where"), expression))(i)?;
+    let (i, group_by) = opt(group_by_clause)(i)?;
+    let (i, having) = opt(preceded(clause("having"), expression))(i)?;
+    let (i, order_by) = opt(order_by_clause)(i)?;
+    let (i, limit_by) = opt(limit_by_clause)(i)?;
+    let (i, limit) = opt(limit_clause)(i)?;
+    let (i, settings) = opt(settings_clause)(i)?;
+    let (i, format) = opt(preceded(clause("format"), sql_identifier))(i)?;
+
+    Ok((i, SelectStatement {
+        with: with.unwrap_or_default(),
+        distinct: distinct.is_some(),
+        fields,
+        from,
+        array_join,
+        joins,
+        prewhere,
+        where_clause,
+        group_by,
+        having,
+        order_by: order_by.unwrap_or_default(),
+        limit_by,
+        limit,
+        settings: settings.unwrap_or_default(),
+        format: format.map(|f| str::from_utf8(f).unwrap().to_string()),
+    }))
+}
+
+fn set_operator(i: &[u8]) -> IResult<&[u8], SetOperator> {
+    alt((
+        map(tuple((tag_no_case("union"), multispace1, tag_no_case("all"))), |_| SetOperator::UnionAll),
+        map(tuple((tag_no_case("union"), multispace1, tag_no_case("distinct"))), |_| SetOperator::UnionDistinct),
+        map(tag_no_case("union"), |_| SetOperator::Union),
+        map(tag_no_case("except"), |_| SetOperator::Except),
+    ))(i)
+}
+
+// SELECT ... or (query)
+fn query_branch(i: &[u8]) -> IResult<&[u8], SelectQuery> {
+    alt((
+        map(select_statement, |s| SelectQuery::Select(Box::new(s))),
+        map(
+            delimited(pair(tag("("), multispace0), nested_selection, pair(multispace0, tag(")"))),
+            |q| SelectQuery::Nested(Box::new(q))
+        ),
+    ))(i)
+}
+
+fn compound(left: SelectQuery, operator: SetOperator, right: SelectQuery) -> SelectQuery {
+    SelectQuery::Compound(Box::new(CompoundSelectStatement { left, operator, right }))
+}
+
+// INTERSECT binds tighter than UNION and EXCEPT, all of them are left-associative
+fn intersect_selection(i: &[u8]) -> IResult<&[u8], SelectQuery> {
+    let (i, first) = query_branch(i)?;
+    fold_many0(
+        preceded(delimited(multispace0, tag_no_case("intersect"), multispace0), query_branch),
+        first,
+        |left, right| compound(left, SetOperator::Intersect, right),
+    )(i)
+}
+
+// A query without the trailing statement terminator, e.g. a subquery
+pub fn nested_selection(i: &[u8]) -> IResult<&[u8], SelectQuery> {
+    let (i, first) = intersect_selection(i)?;
+    fold_many0(
+        pair(delimited(multispace0, set_operator, multispace0), intersect_selection),
+        first,
+        |left, (operator, right)| compound(left, operator, right),
+    )(i)
+}
+
+pub fn selection(i: &[u8]) -> IResult<&[u8], SelectQuery> {
+    nested_selection(i)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use crate::expression::Literal;
+
+    #[test]
+    fn t_select() {
+        let patterns = vec![
+            ( "SELECT 1", "SELECT 1" ),
+            ( "select * from t", "SELECT * FROM t" ),
+            ( "SELECT DISTINCT a, t.* FROM db.t AS t", "SELECT DISTINCT a, t.* FROM db.t AS t" ),
+            ( "SELECT count() c, sum(x) AS s FROM t", "SELECT count() AS c, sum(x) AS s FROM t" ),
+            (
+                "WITH 10 AS n, top AS (SELECT a FROM t LIMIT 5) SELECT a FROM top",
+                "WITH 10 AS n, top AS (SELECT a FROM t LIMIT 5) SELECT a FROM top"
+            ),
+            ( "SELECT number FROM numbers(10) n", "SELECT number FROM numbers(10) AS n" ),
+            ( "SELECT a FROM (SELECT a FROM t) AS s", "SELECT a FROM (SELECT a FROM t) AS s" ),
+            ( "SELECT a FROM t FINAL SAMPLE 1/10 OFFSET 1/2", "SELECT a FROM t FINAL SAMPLE 1/10 OFFSET 1/2" ),
+            ( "SELECT a FROM t SAMPLE 0.1", "SELECT a FROM t SAMPLE 0.1" ),
+            (
+                "SELECT s, x FROM t LEFT ARRAY JOIN arr AS x, nums",
+                "SELECT s, x FROM t LEFT ARRAY JOIN arr AS x, nums"
+            ),
+            (
+                "SELECT a FROM t JOIN u USING id GLOBAL ANY LEFT JOIN v ON f(a) LEFT OUTER JOIN w USING (a, b)",
+                "SELECT a FROM t INNER JOIN u USING (id) GLOBAL ANY LEFT JOIN v ON f(a) LEFT JOIN w USING (a, b)"
+            ),
+            ( "SELECT a FROM t LEFT SEMI JOIN u USING a", "SELECT a FROM t SEMI LEFT JOIN u USING (a)" ),
+            ( "SELECT a FROM t CROSS JOIN u", "SELECT a FROM t CROSS JOIN u" ),
+            (
+                "SELECT a FROM t JOIN u ON t.id = u.id AND u.x IN (1, 2) WHERE a = 1 OR NOT b",
+                "SELECT a FROM t INNER JOIN u ON t.id = u.id AND u.x IN (1, 2) WHERE a = 1 OR NOT b"
+            ),
+            (
+                "SELECT a FROM t PREWHERE b > 1 WHERE c < 2",
+                "SELECT a FROM t PREWHERE b > 1 WHERE c < 2"
+            ),
+            (
+                "SELECT a, count() FROM t GROUP BY a WITH ROLLUP WITH TOTALS HAVING count() > 1",
+                "SELECT a, count() FROM t GROUP BY a WITH ROLLUP WITH TOTALS HAVING count() > 1"
+            ),
+            (
+                "SELECT a FROM t ORDER BY a DESC NULLS FIRST, b COLLATE 'en', c WITH FILL FROM 1 TO 10 STEP 2",
+                "SELECT a FROM t ORDER BY a DESC NULLS FIRST, b COLLATE 'en', c WITH FILL FROM 1 TO 10 STEP 2"
+            ),
+            (
+                "SELECT a FROM t LIMIT 2 BY a LIMIT 5, 10",
+                "SELECT a FROM t LIMIT 2 BY a LIMIT 10 OFFSET 5"
+            ),
+            (
+                "SELECT a FROM t ORDER BY a LIMIT 10 OFFSET 2 WITH TIES",
+                "SELECT a FROM t ORDER BY a LIMIT 10 OFFSET 2 WITH TIES"
+            ),
+            (
+                "SELECT a FROM t SETTINGS max_threads = 8, mode='x' FORMAT JSONEachRow",
+                "SELECT a FROM t SETTINGS max_threads = 8, mode = 'x' FORMAT JSONEachRow"
+            ),
+        ];
+        parse_set_for_test(|i| selection(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns.into_iter().map(|(s, d)| (s, d.to_string())).collect());
+    }
+
+    #[test]
+    fn t_select_ast() {
+        let query = "SELECT a AS x, b FROM db.t FINAL WHERE a > 1 GROUP BY a, b ORDER BY a LIMIT 10";
+        let (rest, select) = select_statement(query.as_bytes()).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(select, SelectStatement {
+            fields: vec![
+                FieldDefinitionExpression::Expression { expr: "a".into(), alias: Some("x".into()) },
+                FieldDefinitionExpression::Expression { expr: "b".into(), alias: None },
+            ],
+            from: Some(FromClause {
+                source: TableExpression::Table(("db", "t").into()),
+                is_final: true,
+                sample: None,
+            }),
+            where_clause: Some(expression(b"a > 1").unwrap().1),
+            group_by: Some(GroupByClause {
+                columns: vec!["a".into(), "b".into()],
+                modifier: None,
+                with_totals: false,
+            }),
+            order_by: vec![OrderByItem {
+                expr: "a".into(),
+                order: None,
+                nulls: None,
+                collate: None,
+                fill: None,
+            }],
+            limit: Some(LimitClause { limit: 10, offset: None, with_ties: false }),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn t_compound_select() {
+        let patterns = vec![
+            ( "SELECT 1 UNION ALL SELECT 2", "SELECT 1 UNION ALL SELECT 2" ),
+            (
+                "select a from t union distinct select b from u union select c from v",
+                "SELECT a FROM t UNION DISTINCT SELECT b FROM u UNION SELECT c FROM v"
+            ),
+            (
+                "(SELECT 1 UNION ALL SELECT 2)EXCEPT( SELECT 3 )",
+                "(SELECT 1 UNION ALL SELECT 2) EXCEPT (SELECT 3)"
+            ),
+            (
+                "SELECT a FROM t WHERE a > 1 INTERSECT SELECT a FROM u ORDER BY a LIMIT 10",
+                "SELECT a FROM t WHERE a > 1 INTERSECT SELECT a FROM u ORDER BY a LIMIT 10"
+            ),
+            (
+                "SELECT a FROM (SELECT a FROM t UNION ALL SELECT b FROM u) AS s",
+                "SELECT a FROM (SELECT a FROM t UNION ALL SELECT b FROM u) AS s"
+            ),
+            (
+                "WITH q AS (SELECT 1 EXCEPT SELECT 2) SELECT * FROM q",
+                "WITH q AS (SELECT 1 EXCEPT SELECT 2) SELECT * FROM q"
+            ),
+        ];
+        parse_set_for_test(|i| selection(i)
+                .map(|(_, o)| ("".as_bytes(), format!("{}", o))),
+            patterns.into_iter().map(|(s, d)| (s, d.to_string())).collect());
+    }
+
+    #[test]
+    fn t_compound_select_associativity() {
+        let select = |n: u64| SelectQuery::Select(Box::new(SelectStatement {
+            fields: vec![FieldDefinitionExpression::Expression {
+                expr: Expr::Literal(Literal::UnsignedInteger(n)),
+                alias: None,
+            }],
+            ..Default::default()
+        }));
+        let parse = |q: &str| {
+            let (rest, query) = selection(q.as_bytes()).unwrap();
+            assert!(rest.is_empty());
+            query
+        };
+
+        assert_eq!(
+            parse("SELECT 1 UNION ALL SELECT 2 EXCEPT SELECT 3"),
+            compound(compound(select(1), SetOperator::UnionAll, select(2)), SetOperator::Except, select(3))
+        );
+        assert_eq!(
+            parse("SELECT 1 UNION ALL SELECT 2 INTERSECT SELECT 3"),
+            compound(select(1), SetOperator::UnionAll, compound(select(2), SetOperator::Intersect, select(3)))
+        );
+        assert_eq!(
+            parse("SELECT 1 UNION ALL (SELECT 2 UNION ALL SELECT 3)"),
+            compound(
+                select(1),
+                SetOperator::UnionAll,
+                SelectQuery::Nested(Box::new(compound(select(2), SetOperator::UnionAll, select(3)))),
+            )
+        );
+    }
+
+    #[test]
+    fn t_select_statement() {
+        let query = parse_query("SELECT a FROM t WHERE b > 1 FORMAT TSV;").unwrap();
+        assert_eq!(format!("{}", query), "SELECT a FROM t WHERE b > 1 FORMAT TSV;");
+        match query {
+            SqlQuery::Select(SelectQuery::Select(ref select)) => assert_eq!(select.format, Some("TSV".into())),
+            _ => panic!("not a SELECT: {:?}", query),
+        }
+
+        let res = parse_statements("SELECT a\nFROM t\nLIMIT 1;\nSELECT b FROM u WHERE;");
+        assert!(res[0].is_ok());
+        assert_eq!(res[1].as_ref().unwrap_err().expected, "end of statement");
+    }
+
+    #[test]
+    fn t_select_unsupported() {
+        let query = parse_query("SELECT count(DISTINCT a), uniq(b) FROM t").unwrap();
+        assert_eq!(format!("{}", query), "SELECT count(DISTINCT a), uniq(b) FROM t;");
+
+        // constructs without an AST node must fail rather than be cut off
+        for (q, found) in &[
+            ("SELECT a::Int32", "`:`"),
+            ("SELECT date '2020-01-01'", "`'2020-01-01'`"),
+            ("SELECT * EXCEPT (a) FROM t", "`EXCEPT`"),
+        ] {
+            assert_eq!(&parse_query(q).unwrap_err().found, found);
+        }
+        assert!(parse_query("SELECT a FROM (SELECT * EXCEPT (a) FROM t)").is_err());
+        assert!(parse_query("SELECT a FROM t WHERE x IN (SELECT a::Int32 FROM u)").is_err());
+    }
+}
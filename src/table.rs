@@ -23,7 +23,7 @@ impl fmt::Display for Table {
     }
 }
 
-impl<'a> From<&'a str> for Table {
+impl From<&str> for Table {
     fn from(t: &str) -> Table {
         Table {
             name: String::from(t),